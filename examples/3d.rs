@@ -8,7 +8,7 @@ use bevy_easy_gif::*;
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(GifPlugin)
+        .add_plugins(GifPlugin::default())
         .add_systems(Startup, setup_scene)
         .add_systems(Update, rotate_cubes)
         .run();
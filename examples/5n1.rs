@@ -19,7 +19,7 @@ pub fn main() {
                     ..default()
                 }),
         )
-        .add_plugins(GifPlugin)
+        .add_plugins(GifPlugin::default())
         .add_systems(Startup, (setup_camera, spawn_gifs, spawn_labels))
         .add_systems(Update, update_labels)
         .run();
@@ -92,6 +92,7 @@ fn spawn_labels(mut commands: Commands) {
         });
 }
 
+#[allow(clippy::type_complexity)]
 fn update_labels(
     mut first_l: Single<&mut Text, (With<First>, With<Left>, Without<Sec>, Without<Right>)>,
     mut sec_l: Single<&mut Text, (With<Sec>, With<Left>, Without<First>, Without<Right>)>,
@@ -17,7 +17,7 @@ pub fn main() {
                     ..default()
                 }),
         )
-        .add_plugins(GifPlugin)
+        .add_plugins(GifPlugin::default())
         .add_systems(Startup, (setup_camera, spawn_gif))
         .run();
 }
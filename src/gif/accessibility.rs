@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+/// Accessibility settings for the players sensitive to flashing or rapid animations.
+/// They apply to all the gifs, except the ones with a [GifEssential].
+///
+/// ```ignore
+/// fn reduce_motion(mut accessibility: ResMut<GifAccessibility>) {
+///     accessibility.max_fps = Some(4.);
+/// }
+/// ```
+#[derive(Resource, Debug, Clone)]
+pub struct GifAccessibility {
+    /// Speed of the gifs, on top of [GifSettings::speed](crate::GifSettings::speed) which also applies
+    /// to the [GifEssential] ones. 1 by default. Negative, infinite and NaN speeds freeze the gifs.
+    pub speed: f32,
+    /// How many times per second, at most, a gif shows a new frame. Their timing is kept:
+    /// frames shown for too short a time are skipped. Unlimited by default.
+    /// It is measured in real time, whatever the speed of the gifs. A cap of 0, negative or NaN
+    /// is ignored, with a warning.
+    pub max_fps: Option<f32>,
+    /// Freeze the gifs on their [GifAsset::poster](crate::GifAsset::poster) frame.
+    /// They go on from where they were once it is disabled.
+    pub reduced_motion: bool,
+}
+
+impl Default for GifAccessibility {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            max_fps: None,
+            reduced_motion: false,
+        }
+    }
+}
+
+/// Insert this component next to a gif that matters to the gameplay, so that the
+/// [GifAccessibility] settings do not apply to it.
+///
+/// ```ignore
+/// commands.spawn((Gif { handle: countdown }, GifEssential));
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct GifEssential;
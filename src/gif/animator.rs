@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::gif::{GifAsset, GifPlayer, target::GifTarget};
+
+/// State machine choosing what a gif entity plays, according to parameters set by the game.
+///
+/// Each state plays a clip, another GIF, or both. Transitions between states happen
+/// when all their conditions hold, either right away or once the current loop is over.
+///
+/// ```ignore
+/// let animator = GifAnimator::new("idle", GifState::clip("idle"))
+///     .with_state("run", GifState::clip("run"))
+///     .with_state("jump", GifState::gif(jump).with_clip("up"))
+///     .with_transition(GifTransition::new("idle", "run").when(GifCondition::Greater("speed".into(), 0.1)))
+///     .with_transition(GifTransition::new("run", "idle").when(GifCondition::Less("speed".into(), 0.1)).at_loop_end())
+///     .with_transition(GifTransition::from_any("jump").when(GifCondition::Trigger("jump".into())));
+/// commands.spawn((Gif { handle: character }, animator));
+///
+/// // later on
+/// animator.set_float("speed", velocity.length());
+/// animator.trigger("jump");
+/// ```
+///
+/// Transitions are checked in the order they were added, before the GIFs are animated.
+#[derive(Component, Debug, Clone)]
+#[require(GifPlayer)]
+pub struct GifAnimator {
+    states: HashMap<String, GifState>,
+    transitions: Vec<GifTransition>,
+    bools: HashMap<String, bool>,
+    floats: HashMap<String, f32>,
+    triggers: HashSet<String>,
+    /// Name of the current state
+    current: String,
+    /// Whether the current state is already playing
+    entered: bool,
+    /// GIF of the entity before the first state, played by the states without their own
+    origin: Option<Handle<GifAsset>>,
+    /// Transition to follow once the current loop is over, as long as its conditions hold
+    pending: Option<usize>,
+}
+
+/// What a [GifAnimator] plays while in a state
+#[derive(Debug, Clone, Default)]
+pub struct GifState {
+    /// GIF to play, or the one the entity had when the animator started if `None`
+    pub handle: Option<Handle<GifAsset>>,
+    /// Clip to play, or the whole GIF if `None`. See [GifAsset::clips].
+    pub clip: Option<String>,
+}
+
+impl GifState {
+    /// Play a clip of the GIF of the entity
+    pub fn clip(name: impl Into<String>) -> Self {
+        Self {
+            handle: None,
+            clip: Some(name.into()),
+        }
+    }
+
+    /// Play another GIF
+    pub fn gif(handle: Handle<GifAsset>) -> Self {
+        Self {
+            handle: Some(handle),
+            clip: None,
+        }
+    }
+
+    /// Only play a clip of the GIF
+    pub fn with_clip(mut self, name: impl Into<String>) -> Self {
+        self.clip = Some(name.into());
+        self
+    }
+}
+
+/// A condition on the parameters of a [GifAnimator]. Unset parameters are `false`, or `0.`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GifCondition {
+    /// The bool parameter has this value
+    Bool(String, bool),
+    /// The trigger was set. It is reset once the transition happens.
+    Trigger(String),
+    /// The float parameter is greater than this value
+    Greater(String, f32),
+    /// The float parameter is less than this value
+    Less(String, f32),
+}
+
+/// When a [GifTransition] happens, once its conditions hold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GifTransitionTiming {
+    /// Switch right away, from the first frame of the new state
+    #[default]
+    Immediate,
+    /// Wait for the current loop to be over
+    AtLoopEnd,
+    /// Switch right away, keeping the position within the clip: the 3rd frame of a clip goes on
+    /// with the 3rd frame of the next one. Useful for clips of the same length, like walking and running.
+    MatchingFrame,
+}
+
+/// A transition between two states of a [GifAnimator]
+#[derive(Debug, Clone)]
+pub struct GifTransition {
+    /// State this transition leaves, or any state if `None`
+    pub from: Option<String>,
+    pub to: String,
+    /// All of them must hold for the transition to happen
+    pub conditions: Vec<GifCondition>,
+    pub timing: GifTransitionTiming,
+}
+
+impl GifTransition {
+    /// A transition from the state `from` to the state `to`
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: Some(from.into()),
+            to: to.into(),
+            conditions: vec![],
+            timing: GifTransitionTiming::Immediate,
+        }
+    }
+
+    /// A transition from any other state to the state `to`
+    pub fn from_any(to: impl Into<String>) -> Self {
+        Self {
+            from: None,
+            to: to.into(),
+            conditions: vec![],
+            timing: GifTransitionTiming::Immediate,
+        }
+    }
+
+    /// Add a condition to the transition
+    pub fn when(mut self, condition: GifCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Wait for the current loop to be over, see [GifTransitionTiming::AtLoopEnd]
+    pub fn at_loop_end(mut self) -> Self {
+        self.timing = GifTransitionTiming::AtLoopEnd;
+        self
+    }
+
+    /// Keep the position within the clip, see [GifTransitionTiming::MatchingFrame]
+    pub fn matching_frame(mut self) -> Self {
+        self.timing = GifTransitionTiming::MatchingFrame;
+        self
+    }
+}
+
+impl GifAnimator {
+    /// An animator starting in the state `name`
+    pub fn new(name: impl Into<String>, state: GifState) -> Self {
+        let name = name.into();
+        Self {
+            states: HashMap::from([(name.clone(), state)]),
+            transitions: vec![],
+            bools: HashMap::new(),
+            floats: HashMap::new(),
+            triggers: HashSet::new(),
+            current: name,
+            entered: false,
+            origin: None,
+            pending: None,
+        }
+    }
+
+    /// Add a state, replacing any state with the same name
+    pub fn with_state(mut self, name: impl Into<String>, state: GifState) -> Self {
+        self.states.insert(name.into(), state);
+        self
+    }
+
+    /// Add a transition, checked after the previous ones
+    pub fn with_transition(mut self, transition: GifTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    /// Name of the current state
+    pub fn state(&self) -> &str {
+        &self.current
+    }
+
+    pub fn set_bool(&mut self, name: impl Into<String>, value: bool) {
+        self.bools.insert(name.into(), value);
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        self.bools.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn set_float(&mut self, name: impl Into<String>, value: f32) {
+        self.floats.insert(name.into(), value);
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        self.floats.get(name).copied().unwrap_or_default()
+    }
+
+    /// Set a trigger, which stays set until a transition depending on it happens.
+    /// A transition waiting for the end of the loop only resets it once it happens.
+    pub fn trigger(&mut self, name: impl Into<String>) {
+        self.triggers.insert(name.into());
+    }
+
+    fn holds(&self, condition: &GifCondition) -> bool {
+        match condition {
+            GifCondition::Bool(name, value) => self.bool(name) == *value,
+            GifCondition::Trigger(name) => self.triggers.contains(name),
+            GifCondition::Greater(name, value) => self.float(name) > *value,
+            GifCondition::Less(name, value) => self.float(name) < *value,
+        }
+    }
+
+    /// Index of the first transition leaving the current state whose conditions hold, if any
+    fn next_transition(&self) -> Option<usize> {
+        self.transitions.iter().position(|transition| {
+            transition
+                .from
+                .as_ref()
+                .is_none_or(|from| *from == self.current)
+                && transition.to != self.current
+                && transition.conditions.iter().all(|c| self.holds(c))
+        })
+    }
+
+    /// Follow the transition `index`, resetting the triggers it depends on
+    fn follow<T: GifTarget>(
+        &mut self,
+        index: usize,
+        target: &mut Mut<T>,
+        player: &mut Mut<GifPlayer>,
+    ) {
+        let transition = &self.transitions[index];
+        let (to, timing) = (transition.to.clone(), transition.timing);
+        for condition in &transition.conditions {
+            if let GifCondition::Trigger(name) = condition {
+                self.triggers.remove(name);
+            }
+        }
+        self.enter(
+            to,
+            target,
+            player,
+            timing == GifTransitionTiming::MatchingFrame,
+        );
+    }
+
+    /// Enter the first state, then follow the transitions whose conditions hold
+    pub(crate) fn update<T: GifTarget>(
+        &mut self,
+        target: &mut Mut<T>,
+        player: &mut Mut<GifPlayer>,
+    ) {
+        if !self.entered {
+            self.entered = true;
+            self.origin = Some(target.handle().clone());
+            self.enter(self.current.clone(), target, player, false);
+        }
+        // A transition waiting for the end of the loop is called off once its conditions no longer hold
+        self.pending = None;
+        match self.next_transition() {
+            Some(index) if self.transitions[index].timing == GifTransitionTiming::AtLoopEnd => {
+                self.pending = Some(index);
+            }
+            Some(index) => self.follow(index, target, player),
+            None => {}
+        }
+    }
+
+    /// Follow the transition waiting for the end of the loop, if any
+    pub(crate) fn end_loop<T: GifTarget>(
+        &mut self,
+        target: &mut Mut<T>,
+        player: &mut Mut<GifPlayer>,
+    ) {
+        if let Some(index) = self.pending.take() {
+            self.follow(index, target, player);
+        }
+    }
+
+    fn enter<T: GifTarget>(
+        &mut self,
+        name: String,
+        target: &mut Mut<T>,
+        player: &mut Mut<GifPlayer>,
+        matching: bool,
+    ) {
+        let Some(state) = self.states.get(&name) else {
+            warn!("Unknown GifAnimator state {name:?}");
+            return;
+        };
+        if let Some(handle) = state.handle.as_ref().or(self.origin.as_ref())
+            && handle != target.handle()
+        {
+            target.set_handle(handle.clone());
+        }
+        player.switch(state.clip.clone(), matching);
+        self.current = name;
+    }
+}
@@ -0,0 +1,82 @@
+use std::{any::TypeId, collections::HashMap, time::Duration};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::gif::GifSettings;
+
+/// Insert this component next to a gif to choose the time driving its playback.
+/// Without it, the [GifSettings::default_clock] is used.
+///
+/// ```ignore
+/// // keeps spinning while the game is paused
+/// commands.spawn((Gif { handle: spinner }, GifClock::Real));
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GifClock {
+    /// [Time<Virtual>], which can be paused and scaled. This is the [Time] of the `Update` schedule.
+    #[default]
+    Virtual,
+    /// [Time<Real>], which keeps going while the game is paused
+    Real,
+    /// [Time<Fixed>]: the gif advances by the fixed timesteps which ran since the last update,
+    /// for deterministic replays
+    Fixed,
+    /// A user-defined [Time<T>], see [GifClock::custom]
+    Custom(TypeId),
+}
+
+impl GifClock {
+    /// Use the [Time<T>] resource, once registered with
+    /// [register_gif_clock](crate::GifAppExt::register_gif_clock).
+    pub fn custom<T: Default + Send + Sync + 'static>() -> Self {
+        Self::Custom(TypeId::of::<T>())
+    }
+}
+
+/// Time elapsed since the last update on the clocks which are not directly readable from `Update`
+#[derive(Resource, Debug, Default)]
+pub(crate) struct GifClockDeltas {
+    /// Fixed timesteps accumulated since the last update
+    pub(crate) fixed_accumulated: Duration,
+    /// Fixed timesteps which ran before this update
+    pub(crate) fixed: Duration,
+    /// Delta of each registered custom clock
+    pub(crate) custom: HashMap<TypeId, Duration>,
+}
+
+/// Time elapsed since the last update, for each [GifClock]
+#[derive(SystemParam)]
+pub(crate) struct GifClocks<'w> {
+    virtual_time: Res<'w, Time<Virtual>>,
+    real_time: Res<'w, Time<Real>>,
+    deltas: Res<'w, GifClockDeltas>,
+    settings: Res<'w, GifSettings>,
+}
+
+impl GifClocks<'_> {
+    /// Time elapsed since the last update on `clock`, or on the default clock,
+    /// scaled by [GifSettings::speed]
+    pub(crate) fn delta(&self, clock: Option<&GifClock>) -> Duration {
+        let delta = match clock.copied().unwrap_or(self.settings.default_clock) {
+            GifClock::Virtual => self.virtual_time.delta(),
+            GifClock::Real => self.real_time.delta(),
+            GifClock::Fixed => self.deltas.fixed,
+            GifClock::Custom(id) => self.deltas.custom.get(&id).copied().unwrap_or_default(),
+        };
+        scale_delta(delta, self.settings.speed)
+    }
+
+    /// Real time elapsed since the last update, whatever the clocks and speeds
+    pub(crate) fn real_delta(&self) -> Duration {
+        self.real_time.delta()
+    }
+}
+
+/// `delta` scaled by `speed`, where NaN, negative and infinite speeds count as 0.
+/// Saturates rather than overflowing.
+pub(crate) fn scale_delta(delta: Duration, speed: f32) -> Duration {
+    if !speed.is_finite() || speed <= 0. {
+        return Duration::ZERO;
+    }
+    Duration::try_from_secs_f64(delta.as_secs_f64() * f64::from(speed)).unwrap_or(Duration::MAX)
+}
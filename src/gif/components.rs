@@ -1,18 +1,25 @@
-use std::time::Duration;
+use std::{borrow::Cow, collections::HashMap, ops::Range, time::Duration};
 
 use bevy::{
-    asset::{AssetLoader, LoadContext, io::Reader},
+    asset::{AssetLoader, LoadContext, RenderAssetUsages, io::Reader},
+    image::ImageSampler,
+    math::Affine2,
     prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
-use gif::{ColorOutput, DecodeOptions, Repeat};
+use gif::{ColorOutput, DecodeOptions, DisposalMethod, Repeat};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Entity used to spawn a [Sprite] with an animated texture.
 /// This is the main and might be the only struct you will use from this crate.
 ///
-/// ```no_run
-/// commands.spawn(Gif { handle: asset_server.load("frog.gif") })
+/// ```ignore
+/// commands.spawn(Gif { handle: asset_server.load("frog.gif") });
 /// ```
+///
+/// The handle can be changed at any time: the new GIF then plays from its first frame,
+/// or from the same progress with [GifKeepProgress].
 #[derive(Component, Debug, Clone)]
 #[require(Sprite, GifPlayer)]
 pub struct Gif {
@@ -29,11 +36,40 @@ pub struct Gif {
 /// The former means: Repeat indefinitely.
 /// The latter: Do not repeat _anymore_.
 /// Ultimately, `remaining` == Some(n: n!= 0) means: Repeat n more time(s).
+///
+/// Rather than editing these fields, prefer the control methods ([GifPlayer::pause],
+/// [GifPlayer::seek_frame]...): their effects are applied by the systems, which update the
+/// displayed frame right away.
 #[derive(Component, Debug, Clone)]
 pub struct GifPlayer {
     pub current: usize,
     pub timer: Timer,
     pub remaining: Option<u16>,
+    /// The [GifAsset] this player was initialized for
+    pub(crate) source: Option<AssetId<GifAsset>>,
+    /// Frame shown by the target, if any. It can be behind `current` while the gif is hidden
+    /// (see [GifCulling::SkipRender](crate::GifCulling::SkipRender)), or for accessibility (see [GifAccessibility](crate::GifAccessibility)).
+    pub(crate) displayed: Option<usize>,
+    /// Time since the target last showed a new frame, see [GifAccessibility::max_fps](crate::GifAccessibility::max_fps)
+    pub(crate) since_shown: Duration,
+    /// Whether the controls (pause, speed, direction) changed since they were last handed over,
+    /// see [GifSyncGroup](crate::GifSyncGroup)
+    pub(crate) controlled: bool,
+    /// Whether the [GifStartOffset](crate::GifStartOffset) was applied, which only happens once
+    pub(crate) offset_applied: bool,
+    paused: bool,
+    speed: f32,
+    direction: GifDirection,
+    /// Whether a [GifDirection::PingPong] loop is on its way back
+    backwards: bool,
+    /// Seek requested with the control methods, applied by the systems
+    seek: Option<GifSeek>,
+    /// Name of the clip being played, see [GifAsset::clips]
+    clip: Option<String>,
+    /// Frames being played: the clip, or the whole GIF
+    range: Range<usize>,
+    /// Loops completed since the GIF (or clip) started
+    loops: u32,
 }
 
 impl Default for GifPlayer {
@@ -42,18 +78,345 @@ impl Default for GifPlayer {
             current: 0,
             timer: Timer::new(Duration::from_millis(100), TimerMode::Repeating),
             remaining: None,
+            source: None,
+            displayed: None,
+            since_shown: Duration::ZERO,
+            controlled: false,
+            offset_applied: false,
+            paused: false,
+            speed: 1.,
+            direction: GifDirection::Forward,
+            backwards: false,
+            seek: None,
+            clip: None,
+            range: 0..0,
+            loops: 0,
         }
     }
 }
 
+/// In which order the frames of a [GifPlayer] are played
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GifDirection {
+    /// From the first frame to the last one
+    #[default]
+    Forward,
+    /// From the last frame to the first one
+    Reverse,
+    /// From the first frame to the last one, then back to the first one.
+    /// A loop is over once the first frame is reached again.
+    PingPong,
+}
+
+/// Where to go, after a call to one of the seek methods of [GifPlayer]
+#[derive(Debug, Clone)]
+enum GifSeek {
+    Frame(usize),
+    Time(Duration),
+    Restart,
+    Progress(f32),
+    /// Clip to play, and how many frames into it
+    Clip(Option<String>, usize),
+}
+
+impl GifPlayer {
+    /// Freeze the animation on its current frame
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.controlled = true;
+    }
+
+    /// Resume the animation after a [GifPlayer::pause]
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.controlled = true;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Scale the playback speed: 2.0 plays twice as fast, 0.5 twice as slow.
+    /// Negative values are clamped to 0, infinite and NaN values are ignored.
+    pub fn set_speed(&mut self, speed: f32) {
+        if !speed.is_finite() {
+            warn!("Ignoring GifPlayer speed {speed}");
+            return;
+        }
+        self.speed = speed.max(0.);
+        self.controlled = true;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Play the frames in the given order
+    ///
+    /// ```ignore
+    /// commands.spawn((Gif { handle }, GifPlayer::default().with_direction(GifDirection::PingPong)));
+    /// ```
+    pub fn with_direction(mut self, direction: GifDirection) -> Self {
+        self.set_direction(direction);
+        self
+    }
+
+    /// Change the order in which frames are played, starting from the current frame
+    pub fn set_direction(&mut self, direction: GifDirection) {
+        self.direction = direction;
+        self.backwards = false;
+        self.controlled = true;
+    }
+
+    pub fn direction(&self) -> GifDirection {
+        self.direction
+    }
+
+    /// Jump to the frame `index`, or to the closest one within the clip (or GIF) being played
+    pub fn seek_frame(&mut self, index: usize) {
+        self.seek = Some(GifSeek::Frame(index));
+    }
+
+    /// Jump to the frame shown `time` after the beginning of a loop of the clip (or GIF) being played.
+    /// Times longer than a loop wrap around.
+    pub fn seek_time(&mut self, time: Duration) {
+        self.seek = Some(GifSeek::Time(time));
+    }
+
+    /// Jump to the frame shown at `progress` (between 0 and 1) of a loop of the clip (or GIF) being played
+    pub(crate) fn seek_progress(&mut self, progress: f32) {
+        self.seek = Some(GifSeek::Progress(progress));
+    }
+
+    /// How far into a loop of the clip (or GIF) being played the player is, between 0 and 1
+    pub(crate) fn progress(&self, gif_asset: &GifAsset) -> f32 {
+        let frames = &gif_asset.frames[self.range.clone()];
+        let total: Duration = frames.iter().map(|frame| frame.duration).sum();
+        let before: Duration = frames
+            .iter()
+            .take(self.current.saturating_sub(self.range.start))
+            .map(|frame| frame.duration)
+            .sum();
+        (before + self.timer.elapsed()).as_secs_f32() / total.as_secs_f32().max(f32::EPSILON)
+    }
+
+    /// Play again from the first frame (the last one in [GifDirection::Reverse]), with all the loops of the GIF.
+    /// This also starts over a GIF whose loops are over.
+    pub fn restart(&mut self) {
+        self.seek = Some(GifSeek::Restart);
+    }
+
+    /// Only play the frames of the clip `name`, looping over them.
+    /// The clip starts over from its first frame, with all the loops of the GIF.
+    ///
+    /// ```ignore
+    /// player.play_clip("run");
+    /// ```
+    ///
+    /// Unknown clips play the whole GIF. See [GifAsset::clips].
+    pub fn play_clip(&mut self, name: impl Into<String>) {
+        self.seek = Some(GifSeek::Clip(Some(name.into()), 0));
+    }
+
+    /// Play the whole GIF again after a [GifPlayer::play_clip], from its first frame
+    pub fn stop_clip(&mut self) {
+        self.seek = Some(GifSeek::Clip(None, 0));
+    }
+
+    /// Start with the clip `name` instead of the whole GIF
+    ///
+    /// ```ignore
+    /// commands.spawn((Gif { handle }, GifPlayer::default().with_clip("idle")));
+    /// ```
+    pub fn with_clip(mut self, name: impl Into<String>) -> Self {
+        self.clip = Some(name.into());
+        self
+    }
+
+    /// Name of the clip being played, if any
+    pub fn clip(&self) -> Option<&str> {
+        self.clip.as_deref()
+    }
+
+    /// How many loops were completed since the GIF (or clip) started, up to [u32::MAX]
+    pub fn loop_index(&self) -> u32 {
+        self.loops
+    }
+
+    /// Play another clip (or the whole GIF), from its first frame,
+    /// or from the same position as within the current one if `matching`.
+    /// The clip is set right away, in case the GIF changes too.
+    pub(crate) fn switch(&mut self, clip: Option<String>, matching: bool) {
+        let offset = match matching {
+            true => self.current.saturating_sub(self.range.start),
+            false => 0,
+        };
+        self.clip = clip.clone();
+        self.seek = Some(GifSeek::Clip(clip, offset));
+    }
+
+    /// Play a [GifAsset] (or its clip) from its first frame, with all its loops
+    pub(crate) fn start(&mut self, gif_asset: &GifAsset) {
+        self.range = match &self.clip {
+            Some(name) => gif_asset.clip(name).unwrap_or_else(|| {
+                warn!("Unknown GIF clip {name:?}, playing the whole GIF instead");
+                0..gif_asset.frames.len()
+            }),
+            None => 0..gif_asset.frames.len(),
+        };
+        self.current = self.first_frame();
+        self.timer = Timer::new(
+            gif_asset.frames[self.current].duration,
+            TimerMode::Repeating,
+        );
+        self.remaining = gif_asset.times;
+        self.backwards = false;
+        self.loops = 0;
+    }
+
+    /// Index of the frame a loop starts with, according to the direction
+    fn first_frame(&self) -> usize {
+        match self.direction {
+            GifDirection::Forward | GifDirection::PingPong => self.range.start,
+            GifDirection::Reverse => self.range.end - 1,
+        }
+    }
+
+    /// Move on to the next frame of the clip (or GIF) according to the direction.
+    /// Return whether this ended a loop, the current frame being the first one of the next loop.
+    pub(crate) fn step(&mut self) -> bool {
+        let Range { start, end } = self.range;
+        let (next, looped) = match self.direction {
+            GifDirection::Forward if self.current + 1 >= end => (start, true),
+            GifDirection::Forward => (self.current + 1, false),
+            GifDirection::Reverse if self.current <= start => (end - 1, true),
+            GifDirection::Reverse => (self.current - 1, false),
+            GifDirection::PingPong => {
+                if !self.backwards && self.current + 1 >= end {
+                    // turnaround
+                    self.backwards = true;
+                }
+                if self.backwards {
+                    let next = self.current.saturating_sub(1).max(start);
+                    self.backwards = next != start;
+                    (next, next == start)
+                } else {
+                    (self.current + 1, false)
+                }
+            }
+        };
+        self.current = next;
+        if looped {
+            self.loops = self.loops.saturating_add(1);
+        }
+        looped
+    }
+
+    /// How long a loop of the clip (or GIF) lasts, according to the direction
+    pub(crate) fn loop_duration(&self, gif_asset: &GifAsset) -> Duration {
+        let frames = &gif_asset.frames[self.range.clone()];
+        let total: Duration = frames.iter().map(|frame| frame.duration).sum();
+        match self.direction {
+            GifDirection::PingPong if frames.len() > 1 => {
+                // the first and last frames are only played once
+                total * 2 - frames[0].duration - frames[frames.len() - 1].duration
+            }
+            _ => total,
+        }
+    }
+
+    /// Whether the frames being played are within `gif_asset`, which is not the case
+    /// once it has been modified with fewer frames, until the player starts over
+    pub(crate) fn fits(&self, gif_asset: &GifAsset) -> bool {
+        self.range.end <= gif_asset.frames.len()
+    }
+
+    /// How many frames a loop of the clip (or GIF) plays, according to the direction
+    pub(crate) fn loop_len(&self) -> usize {
+        match self.direction {
+            GifDirection::PingPong if self.range.len() > 1 => self.range.len() * 2 - 2,
+            _ => self.range.len(),
+        }
+    }
+
+    /// Count `loops` loops as completed, without stepping through their frames.
+    /// A whole loop brings the player back to the same frame, so only the counters change.
+    pub(crate) fn skip_loops(&mut self, loops: u128) {
+        self.loops = self
+            .loops
+            .saturating_add(u32::try_from(loops).unwrap_or(u32::MAX));
+        if let Some(remaining) = self.remaining {
+            let skipped = u16::try_from(loops).unwrap_or(u16::MAX);
+            self.remaining = Some(remaining.saturating_sub(skipped));
+        }
+    }
+
+    /// Hand the seek (or clip) requested on this player over to `other`, along with the
+    /// controls changed since the last time, if any
+    pub(crate) fn forward_controls(&mut self, other: &mut GifPlayer) {
+        if let Some(seek) = self.seek.take() {
+            other.seek = Some(seek);
+        }
+        if std::mem::take(&mut self.controlled) {
+            other.paused = self.paused;
+            other.speed = self.speed;
+            if other.direction != self.direction {
+                other.direction = self.direction;
+                other.backwards = false;
+            }
+        }
+    }
+
+    /// Apply the seek requested with the control methods, if any. Return whether it happened.
+    pub(crate) fn apply_seek(&mut self, gif_asset: &GifAsset) -> bool {
+        let Some(seek) = self.seek.take() else {
+            return false;
+        };
+        let (index, elapsed) = match seek {
+            GifSeek::Frame(index) => (
+                index.clamp(self.range.start, self.range.end - 1),
+                Duration::ZERO,
+            ),
+            GifSeek::Time(time) => gif_asset.frame_in(self.range.clone(), time),
+            GifSeek::Progress(progress) => {
+                let total: Duration = gif_asset.frames[self.range.clone()]
+                    .iter()
+                    .map(|frame| frame.duration)
+                    .sum();
+                gif_asset.frame_in(self.range.clone(), total.mul_f32(progress))
+            }
+            GifSeek::Restart => {
+                self.start(gif_asset);
+                return true;
+            }
+            GifSeek::Clip(clip, offset) => {
+                self.clip = clip;
+                self.start(gif_asset);
+                if offset == 0 {
+                    return true;
+                }
+                (self.range.start + offset % self.range.len(), Duration::ZERO)
+            }
+        };
+        self.current = index;
+        self.timer.set_duration(gif_asset.frames[index].duration);
+        self.timer.set_elapsed(elapsed);
+        true
+    }
+}
+
 /// Contains the data of one frame of a GIF
 ///
+/// Frames are complete images of the size of the GIF logical screen, even if the file
+/// itself only stores the part of the frame which changed.
+///
 /// What really distinguish this from using a [TextureAtlas] is the unique [Duration] of each frame,
 /// stored within the asset.
 #[derive(Debug, Clone)]
 pub struct GifFrame {
     pub width: u32,
     pub height: u32,
+    /// Empty if the asset was loaded without [GifLoaderSettings::keep_frame_data]
     pub rgba: Vec<u8>,
     pub duration: Duration,
 }
@@ -63,11 +426,249 @@ pub struct GifFrame {
 /// Careful: `times` represents the raw value of the GIF repeat metadata, which can
 /// be interpreted as "how many times will I _repeat_", with an emphasis on _repeat_.
 /// For a GIF that plays a total of 5 loops, this value is going to be 4.
+///
+/// The gifs playing it start over when it is modified, such as when it is hot reloaded, or
+/// changed with [Assets::get_mut]. Use [Assets::get_mut_untracked] to add clips or markers
+/// without restarting them.
 #[derive(Asset, TypePath, Debug, Clone)]
 pub struct GifAsset {
     pub frames: Vec<GifFrame>,
+    /// One image per frame, loaded as labeled sub-assets: `anim.gif#frame0`, `anim.gif#frame1`...
     pub handles: Vec<Handle<Image>>,
     pub times: Option<u16>,
+    /// Frames packed in a single texture, only in atlas mode. `handles` is then empty.
+    pub atlas: Option<GifAtlas>,
+    /// Named ranges of frames, which can be played on their own with [GifPlayer::play_clip].
+    ///
+    /// They come from [GifLoaderSettings::clips], and can be added afterwards with [GifAsset::add_clip].
+    pub clips: HashMap<String, Range<usize>>,
+    /// Markers of the frames, by frame index. A [GifFrameEvent](crate::GifFrameEvent) is triggered
+    /// for each of them when their frame is played.
+    ///
+    /// They come from [GifLoaderSettings::markers] and [GifLoaderSettings::comment_markers],
+    /// and can be added afterwards with [GifAsset::add_marker].
+    pub markers: HashMap<usize, Vec<String>>,
+    /// Frame standing for the whole GIF, shown by the gifs frozen with
+    /// [GifAccessibility::reduced_motion](crate::GifAccessibility::reduced_motion).
+    /// It comes from [GifLoaderSettings::poster].
+    pub poster: usize,
+}
+
+/// All the frames of a [GifAsset] packed in a single [Image], laid out in a grid.
+///
+/// Both the image and the layout are labeled sub-assets: `anim.gif#atlas` and `anim.gif#atlas_layout`.
+/// See [GifLoaderSettings::atlas].
+#[derive(Debug, Clone)]
+pub struct GifAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl GifAtlas {
+    /// Largest width and height of the packed texture. GPUs cannot sample larger 2d textures,
+    /// GIFs which do not fit fail to load: use [GifLoaderSettings::frames] to split them.
+    pub const MAX_SIZE: u32 = 8192;
+
+    /// [TextureAtlas] showing the frame `index`
+    pub fn texture_atlas(&self, index: usize) -> TextureAtlas {
+        TextureAtlas {
+            layout: self.layout.clone(),
+            index,
+        }
+    }
+
+    /// Transform mapping the whole uv space onto the frame `index` within the atlas,
+    /// for materials which cannot use a [TextureAtlas].
+    pub fn uv_transform(&self, index: usize) -> Affine2 {
+        let (column, row) = (index as u32 % self.columns, index as u32 / self.columns);
+        let scale = Vec2::new(1. / self.columns as f32, 1. / self.rows as f32);
+        Affine2::from_scale_angle_translation(
+            scale,
+            0.,
+            Vec2::new(column as f32, row as f32) * scale,
+        )
+    }
+}
+
+impl GifAsset {
+    /// Total duration of a loop
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    /// Index of the frame shown `time` after the beginning of a loop, and for how long it has been shown.
+    /// Times longer than a loop wrap around.
+    pub fn frame_at(&self, time: Duration) -> (usize, Duration) {
+        self.frame_in(0..self.frames.len(), time)
+    }
+
+    /// Same as [GifAsset::frame_at], for a loop over the frames within `range`
+    pub(crate) fn frame_in(&self, range: Range<usize>, time: Duration) -> (usize, Duration) {
+        let frames = &self.frames[range.clone()];
+        let total: Duration = frames.iter().map(|frame| frame.duration).sum();
+        let mut time = Duration::from_nanos((time.as_nanos() % total.as_nanos().max(1)) as u64);
+        for (index, frame) in range.clone().zip(frames) {
+            if time < frame.duration {
+                return (index, time);
+            }
+            time -= frame.duration;
+        }
+        (range.start, Duration::ZERO)
+    }
+
+    /// Frames of the clip `name`, if any
+    pub fn clip(&self, name: &str) -> Option<Range<usize>> {
+        self.clips.get(name).cloned()
+    }
+
+    /// Define the clip `name` over the frames within `range`, replacing any clip with the same name.
+    ///
+    /// Panics if the range is empty or out of the frames of the GIF.
+    pub fn add_clip(&mut self, name: impl Into<String>, range: Range<usize>) {
+        assert!(
+            range.start < range.end && range.end <= self.frames.len(),
+            "Clip {range:?} is out of the {} frames of the gif",
+            self.frames.len()
+        );
+        self.clips.insert(name.into(), range);
+    }
+
+    /// Markers of the frame `index`
+    pub fn markers(&self, index: usize) -> &[String] {
+        self.markers.get(&index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add the marker `name` to the frame `index`
+    ///
+    /// ```ignore
+    /// gif_asset.add_marker(3, "footstep");
+    /// ```
+    ///
+    /// Panics if the frame is out of the GIF.
+    pub fn add_marker(&mut self, index: usize, name: impl Into<String>) {
+        assert!(
+            index < self.frames.len(),
+            "Frame {index} is out of the {} frames of the gif",
+            self.frames.len()
+        );
+        self.markers.entry(index).or_default().push(name.into());
+    }
+}
+
+/// How many times a GIF repeats, used to override the metadata of the file.
+///
+/// Just like [GifAsset::times], `Finite(n)` means the GIF plays once, then _repeats_ n times.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GifRepeat {
+    Infinite,
+    Finite(u16),
+}
+
+impl GifRepeat {
+    /// Convert to the [GifAsset::times] representation
+    fn times(self) -> Option<u16> {
+        match self {
+            GifRepeat::Infinite => None,
+            GifRepeat::Finite(n) => Some(n),
+        }
+    }
+}
+
+/// Settings used by the asset loader when loading a GIF file.
+///
+/// They can be given with [AssetServer::load_with_settings]:
+///
+/// ```ignore
+/// let handle: Handle<GifAsset> = asset_server.load_with_settings(
+///     "pixel_art.gif",
+///     |settings: &mut GifLoaderSettings| settings.sampler = ImageSampler::nearest(),
+/// );
+/// ```
+///
+/// Or in a `.meta` file next to the asset, `pixel_art.gif.meta` here:
+///
+/// ```ron
+/// (
+///     meta_format_version: "1.0",
+///     asset: Load(
+///         loader: "bevy_easy_gif::gif::components::GifLoader",
+///         settings: (
+///             repeat: Some(Infinite),
+///             frames: Some((start: 0, end: 16)),
+///             clips: {
+///                 "idle": (start: 0, end: 8),
+///                 "run": (start: 8, end: 16),
+///             },
+///             markers: {
+///                 11: ["footstep"],
+///             },
+///             poster: 4,
+///         ),
+///     ),
+/// )
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GifLoaderSettings {
+    /// [ImageSampler] of the frame images. Use [ImageSampler::nearest] for pixel art.
+    pub sampler: ImageSampler,
+    /// Whether the frames are in sRGB color space, or linear.
+    pub is_srgb: bool,
+    /// Where the frame images will be used, see [RenderAssetUsages].
+    pub asset_usage: RenderAssetUsages,
+    /// Ignore the repeat metadata of the file, and use this instead.
+    pub repeat: Option<GifRepeat>,
+    /// Frames shorter than this last this long instead.
+    /// Some GIF files use (very) short delays that browsers would not honor.
+    pub min_frame_delay: Duration,
+    /// Only keep the frames within this range.
+    pub frames: Option<Range<usize>>,
+    /// Named ranges of frames, see [GifAsset::clips].
+    /// Indices are relative to the frames kept with `frames`.
+    pub clips: HashMap<String, Range<usize>>,
+    /// Markers of the frames, by frame index, see [GifAsset::markers].
+    /// Indices are relative to the frames kept with `frames`.
+    pub markers: HashMap<usize, Vec<String>>,
+    /// Read markers from the comments of the GIF file: each comment marks the frame following it.
+    ///
+    /// Disabled by default, as many tools write their name in a comment.
+    pub comment_markers: bool,
+    /// Frame standing for the whole GIF, see [GifAsset::poster]. The first one by default.
+    /// Its index is relative to the frames kept with `frames`.
+    pub poster: usize,
+    /// Pack all the frames into a single texture, see [GifAtlas].
+    ///
+    /// Instead of swapping images, only the [TextureAtlas] index changes from one frame to another,
+    /// which allows sprites showing the same GIF to be batched together.
+    /// The texture can be at most [GifAtlas::MAX_SIZE] wide and high.
+    pub atlas: bool,
+    /// Keep the RGBA data of the frames in the [GifAsset] and the images, on the CPU side.
+    ///
+    /// Disable it to only keep the durations and dimensions of the frames: images are then
+    /// [RenderAssetUsages::RENDER_WORLD] only, and their data is freed once uploaded to the GPU.
+    /// It overrides `asset_usage`.
+    pub keep_frame_data: bool,
+}
+
+impl Default for GifLoaderSettings {
+    fn default() -> Self {
+        Self {
+            sampler: ImageSampler::Default,
+            is_srgb: true,
+            asset_usage: RenderAssetUsages::all(),
+            repeat: None,
+            min_frame_delay: Duration::ZERO,
+            frames: None,
+            clips: HashMap::new(),
+            markers: HashMap::new(),
+            comment_markers: false,
+            poster: 0,
+            atlas: false,
+            keep_frame_data: true,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -81,72 +682,393 @@ pub(crate) enum GifLoaderError {
     /// A data error
     #[error("Decoded gif frame size mismatch: {0} != {1}")]
     SizeMismatch(usize, usize),
+    /// A settings error
+    #[error("Frame range {0:?} is out of the {1} frames of the gif")]
+    EmptyFrameRange(Range<usize>, usize),
+    /// A settings error
+    #[error("Clip {0:?} {1:?} is out of the {2} frames of the gif")]
+    EmptyClip(String, Range<usize>, usize),
+    /// A settings error
+    #[error("Marker {0:?} of frame {1} is out of the {2} frames of the gif")]
+    MarkerOutOfBounds(String, usize, usize),
+    /// A settings error
+    #[error("Poster frame {0} is out of the {1} frames of the gif")]
+    PosterOutOfBounds(usize, usize),
+    /// A settings error
+    #[error("Atlas of {0}x{1} pixels is larger than {max}x{max}", max = GifAtlas::MAX_SIZE)]
+    AtlasTooLarge(u32, u32),
 }
 
 /// Allow to load GIF files properly with the AssetServer
 #[derive(Default, TypePath)]
-pub(crate) struct GifLoader;
+pub(crate) struct GifLoader {
+    /// Sampler of the GIFs without a [GifLoaderSettings::sampler], see [GifPlugin::default_sampler](crate::GifPlugin::default_sampler)
+    pub(crate) default_sampler: ImageSampler,
+}
 
 impl AssetLoader for GifLoader {
     type Asset = GifAsset;
-    type Settings = bool;
+    type Settings = GifLoaderSettings;
     type Error = GifLoaderError;
 
     async fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
+        let settings = match (&settings.sampler, &self.default_sampler) {
+            (ImageSampler::Default, ImageSampler::Descriptor(_)) => Cow::Owned(GifLoaderSettings {
+                sampler: self.default_sampler.clone(),
+                ..settings.clone()
+            }),
+            _ => Cow::Borrowed(settings),
+        };
+
+        let (mut frames, mut times) = Self::decode(&bytes)?;
+        let mut markers = match settings.comment_markers {
+            true => comment_markers(&bytes),
+            false => HashMap::new(),
+        };
+
+        // Frames are composited from the previous ones, so they are all decoded before being dropped
+        if let Some(range) = &settings.frames {
+            let total = frames.len();
+            if range.start >= range.end || range.end > total {
+                return Err(Self::Error::EmptyFrameRange(range.clone(), total));
+            }
+            frames.truncate(range.end);
+            frames.drain(..range.start);
+            markers = markers
+                .into_iter()
+                .filter(|(index, _)| range.contains(index))
+                .map(|(index, names)| (index - range.start, names))
+                .collect();
+        }
+        for frame in frames.iter_mut() {
+            frame.duration = frame.duration.max(settings.min_frame_delay);
+        }
+        if let Some(repeat) = settings.repeat {
+            times = repeat.times();
+        }
+        for (name, range) in settings.clips.iter() {
+            if range.start >= range.end || range.end > frames.len() {
+                return Err(Self::Error::EmptyClip(
+                    name.clone(),
+                    range.clone(),
+                    frames.len(),
+                ));
+            }
+        }
+        for (&index, names) in settings.markers.iter() {
+            if index >= frames.len() {
+                return Err(Self::Error::MarkerOutOfBounds(
+                    names.join(", "),
+                    index,
+                    frames.len(),
+                ));
+            }
+            markers
+                .entry(index)
+                .or_default()
+                .extend(names.iter().cloned());
+        }
+        if settings.poster >= frames.len() {
+            return Err(Self::Error::PosterOutOfBounds(
+                settings.poster,
+                frames.len(),
+            ));
+        }
+
+        // Build the frame textures as labeled sub-assets, so they are loaded (and unloaded) with the gif
+        let mut handles = vec![];
+        let mut atlas = None;
+        if settings.atlas {
+            atlas_grid(frames[0].width, frames[0].height, frames.len())?;
+            let (image, layout) = build_atlas(&frames, &settings);
+            atlas = Some(GifAtlas {
+                image: load_context.add_labeled_asset("atlas".to_string(), image),
+                columns: layout.size.x / layout.textures[0].width(),
+                rows: layout.size.y / layout.textures[0].height(),
+                layout: load_context.add_labeled_asset("atlas_layout".to_string(), layout),
+            });
+        } else {
+            for (i, frame) in frames.iter().enumerate() {
+                let image = build_image(frame.width, frame.height, &frame.rgba, &settings);
+                handles.push(load_context.add_labeled_asset(format!("frame{i}"), image));
+            }
+        }
+        if !settings.keep_frame_data {
+            for frame in frames.iter_mut() {
+                frame.rgba = vec![];
+            }
+        }
+
+        // Create the GifAsset and set it as the default loaded asset
+        let asset = GifAsset {
+            frames,
+            handles,
+            times,
+            atlas,
+            clips: settings.clips.clone(),
+            markers,
+            poster: settings.poster,
+        };
+        Ok(asset)
+    }
 
+    fn extensions(&self) -> &[&str] {
+        &["gif"]
+    }
+}
+
+impl GifLoader {
+    /// Decode raw GIF bytes into complete frames, and read the repeat metadata.
+    ///
+    /// GIF frames are not necessarily full images: optimized files only store the sub-rectangle
+    /// that changed since the previous frame, and tell the decoder what to do with it afterwards
+    /// (its [DisposalMethod]). Each frame is drawn onto a canvas the size of the logical screen,
+    /// so every [GifFrame] ends up being a complete, same-sized image.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<(Vec<GifFrame>, Option<u16>), GifLoaderError> {
         let mut decoder = DecodeOptions::new();
         decoder.set_color_output(ColorOutput::RGBA);
         let mut decoder = decoder.read_info(std::io::Cursor::new(bytes))?;
 
+        let mut canvas = Canvas::new(decoder.width() as u32, decoder.height() as u32);
         let mut frames = Vec::new();
         while let Some(frame) = decoder.read_next_frame()? {
             let width = frame.width as u32;
             let height = frame.height as u32;
-            let rgba = frame.buffer.to_vec();
+            let rgba = &frame.buffer;
 
             // Make sure data is not truncated or smth
             if rgba.len() != (width as usize) * (height as usize) * 4 {
-                return Err(Self::Error::SizeMismatch(
+                return Err(GifLoaderError::SizeMismatch(
                     rgba.len(),
                     (width as usize) * (height as usize) * 4,
                 ));
             }
 
+            // Some encoders write a 0x0 logical screen, fallback on the first frame bounds
+            if frames.is_empty() && canvas.is_empty() {
+                canvas = Canvas::new(frame.left as u32 + width, frame.top as u32 + height);
+            }
+
+            // Remember what was under the frame if we have to restore it afterwards
+            let previous = (frame.dispose == DisposalMethod::Previous).then(|| canvas.rgba.clone());
+            canvas.draw(frame.left as u32, frame.top as u32, width, height, rgba);
+
             // frame.delay is in 1/100th of a second, per [GIF spec](https://docs.rs/gif/latest/gif/struct.Frame.html#structfield.delay)
             let ms = (frame.delay as u64).saturating_mul(10);
             let duration = Duration::from_millis(ms.max(1)); // avoid 0 ms frames
 
             frames.push(GifFrame {
-                width,
-                height,
-                rgba,
+                width: canvas.width,
+                height: canvas.height,
+                rgba: canvas.rgba.clone(),
                 duration,
             });
+
+            match frame.dispose {
+                DisposalMethod::Background => {
+                    // Browsers restore to transparent rather than to the background color
+                    canvas.clear(frame.left as u32, frame.top as u32, width, height);
+                }
+                DisposalMethod::Previous => {
+                    if let Some(previous) = previous {
+                        canvas.rgba = previous;
+                    }
+                }
+                DisposalMethod::Keep | DisposalMethod::Any => {}
+            }
         }
 
         let times = match decoder.repeat() {
             Repeat::Infinite => None,
             Repeat::Finite(n) => Some(n),
         };
+        Ok((frames, times))
+    }
+}
 
-        // Create the GifAsset and set it as the default loaded asset
-        let asset = GifAsset {
-            frames,
-            handles: vec![], // will be loaded in `initialize_gifs`
-            times,
-        };
-        Ok(asset)
+/// Read the comment extensions of a GIF file, as markers of the frame following each of them.
+///
+/// The decoder skips comments, so the blocks of the file are walked through here.
+pub(crate) fn comment_markers(bytes: &[u8]) -> HashMap<usize, Vec<String>> {
+    /// Size of the color table following a descriptor with these flags
+    fn color_table(flags: u8) -> usize {
+        match flags & 0x80 {
+            0 => 0,
+            _ => 3 << ((flags & 0x07) + 1),
+        }
+    }
+    /// Walk through the data sub-blocks starting at `i`, up to the empty one.
+    /// Return the position following them, and their data if `read`.
+    fn sub_blocks(bytes: &[u8], mut i: usize, read: bool) -> (usize, Vec<u8>) {
+        let mut data = vec![];
+        while let Some(&len) = bytes.get(i) {
+            i += 1;
+            if len == 0 {
+                break;
+            }
+            if read && let Some(block) = bytes.get(i..i + len as usize) {
+                data.extend_from_slice(block);
+            }
+            i += len as usize;
+        }
+        (i, data)
     }
 
-    fn extensions(&self) -> &[&str] {
-        &["gif"]
+    let mut markers: HashMap<usize, Vec<String>> = HashMap::new();
+    // Header and logical screen descriptor
+    let mut i = 13 + bytes.get(10).map_or(0, |&flags| color_table(flags));
+    let mut frame = 0;
+    while let Some(&block) = bytes.get(i) {
+        match block {
+            // Extension
+            0x21 => {
+                let comment = bytes.get(i + 1) == Some(&0xFE);
+                let (next, data) = sub_blocks(bytes, i + 2, comment);
+                if comment {
+                    let text = String::from_utf8_lossy(&data).trim().to_string();
+                    markers.entry(frame).or_default().push(text);
+                }
+                i = next;
+            }
+            // Image descriptor, then the minimum code size and the image data
+            0x2C => {
+                let flags = bytes.get(i + 9).copied().unwrap_or_default();
+                i += 10 + color_table(flags) + 1;
+                i = sub_blocks(bytes, i, false).0;
+                frame += 1;
+            }
+            // Trailer, or something unexpected which the decoder already accepted
+            _ => break,
+        }
+    }
+    markers
+}
+
+/// Build the [Image] of a frame, or of the atlas, with the loader settings
+fn build_image(width: u32, height: u32, rgba: &[u8], settings: &GifLoaderSettings) -> Image {
+    let format = if settings.is_srgb {
+        TextureFormat::Rgba8UnormSrgb
+    } else {
+        TextureFormat::Rgba8Unorm
+    };
+    let asset_usage = if settings.keep_frame_data {
+        settings.asset_usage
+    } else {
+        RenderAssetUsages::RENDER_WORLD
+    };
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba,
+        format,
+        asset_usage,
+    );
+    image.sampler = settings.sampler.clone();
+    image
+}
+
+/// Pack all the frames in a grid, as square as possible.
+fn build_atlas(frames: &[GifFrame], settings: &GifLoaderSettings) -> (Image, TextureAtlasLayout) {
+    // All frames have the size of the logical screen
+    let (width, height) = (frames[0].width, frames[0].height);
+    let (columns, rows) =
+        atlas_grid(width, height, frames.len()).expect("atlas size is checked by the loader");
+
+    let row_len = (width * 4) as usize;
+    let atlas_row_len = row_len * columns as usize;
+    let mut rgba = vec![0; atlas_row_len * (height * rows) as usize];
+    for (i, frame) in frames.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        for y in 0..height {
+            let src = y as usize * row_len;
+            let dst = (row * height + y) as usize * atlas_row_len + column as usize * row_len;
+            rgba[dst..dst + row_len].copy_from_slice(&frame.rgba[src..src + row_len]);
+        }
+    }
+
+    let image = build_image(width * columns, height * rows, &rgba, settings);
+    let layout =
+        TextureAtlasLayout::from_grid(UVec2::new(width, height), columns, rows, None, None);
+    (image, layout)
+}
+
+/// Columns and rows of the grid packing `count` frames of `width`x`height` pixels in an atlas,
+/// as square as possible. Fails if the atlas is larger than [GifAtlas::MAX_SIZE].
+pub(crate) fn atlas_grid(
+    width: u32,
+    height: u32,
+    count: usize,
+) -> Result<(u32, u32), GifLoaderError> {
+    let columns = (count as f32).sqrt().ceil() as u32;
+    let rows = (count as u32).div_ceil(columns);
+    let (atlas_width, atlas_height) = (width * columns, height * rows);
+    if atlas_width > GifAtlas::MAX_SIZE || atlas_height > GifAtlas::MAX_SIZE {
+        return Err(GifLoaderError::AtlasTooLarge(atlas_width, atlas_height));
+    }
+    Ok((columns, rows))
+}
+
+/// RGBA image of the size of the GIF logical screen, on which frames are composited.
+struct Canvas {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            rgba: vec![0; (width as usize) * (height as usize) * 4],
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rgba.is_empty()
+    }
+
+    /// Index of the first byte of the pixel at (x, y), if it lies within the canvas
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| ((y as usize) * (self.width as usize) + x as usize) * 4)
+    }
+
+    /// Draw a frame sub-image at the given offset. Transparent pixels leave the canvas untouched.
+    fn draw(&mut self, left: u32, top: u32, width: u32, height: u32, rgba: &[u8]) {
+        for y in 0..height {
+            for x in 0..width {
+                let src = ((y as usize) * (width as usize) + x as usize) * 4;
+                if rgba[src + 3] == 0 {
+                    continue;
+                }
+                if let Some(dst) = self.index(left + x, top + y) {
+                    self.rgba[dst..dst + 4].copy_from_slice(&rgba[src..src + 4]);
+                }
+            }
+        }
+    }
+
+    /// Reset an area of the canvas to fully transparent pixels
+    fn clear(&mut self, left: u32, top: u32, width: u32, height: u32) {
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(dst) = self.index(left + x, top + y) {
+                    self.rgba[dst..dst + 4].fill(0);
+                }
+            }
+        }
     }
 }
 
@@ -156,9 +1078,35 @@ impl AssetLoader for GifLoader {
 /// See [despawn example](examples/despawn.rs)
 ///
 /// It has no effect on infinite-looping GIF files.
+/// This is the same as [GifOnFinish::Despawn].
 #[derive(Component)]
 pub struct GifDespawn;
 
+/// Insert this component next to a non-infinite [Gif] to choose what happens
+/// when its loops are over. Without it, the GIF goes back to its first frame and stops there.
+///
+/// ```ignore
+/// commands.spawn((Gif { handle: intro }, GifOnFinish::Switch(idle)));
+/// ```
+///
+/// It has no effect on infinite-looping GIF files.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub enum GifOnFinish {
+    /// Despawn the entity, and its children
+    Despawn,
+    /// Remove the gif components from the entity, which keeps showing the last frame
+    Remove,
+    /// Hide the entity with [Visibility::Hidden]
+    Hide,
+    /// Stop on the last frame of the loop
+    HoldLastFrame,
+    /// Go back to the first frame of the loop, and stop there
+    #[default]
+    ResetToFirstFrame,
+    /// Play another GIF instead
+    Switch(Handle<GifAsset>),
+}
+
 /// Ui component to display a gif file.
 ///
 /// Works the same than [Gif]
@@ -172,9 +1120,105 @@ pub struct GifNode {
 ///
 /// It needs to be spawned alongside a [Mesh3d].
 ///
+/// The material is copied when the GIF starts playing, so that several [Gif3d] can share
+/// a material while showing different frames. See [GifSharedMaterial] to animate it in place instead.
+///
 /// Works almost the same than [Gif]
 #[derive(Component, Debug, Clone)]
 #[require(MeshMaterial3d<StandardMaterial>, GifPlayer)]
 pub struct Gif3d {
     pub handle: Handle<GifAsset>,
 }
+
+/// 2d component to display a gif file on a 2d mesh, such as a waving flag.
+///
+/// It needs to be spawned alongside a [Mesh2d].
+/// Like [Gif3d], the material is copied unless the entity has a [GifSharedMaterial].
+///
+/// Works almost the same than [Gif]
+#[derive(Component, Debug, Clone)]
+#[require(MeshMaterial2d<ColorMaterial>, GifPlayer)]
+pub struct Gif2dMesh {
+    pub handle: Handle<GifAsset>,
+}
+
+/// Component to display a gif file on any [Material] of a 3d object, such as a custom shader.
+///
+/// It needs to be spawned alongside a [Mesh3d] and a [MeshMaterial3d]. Each slot is a texture
+/// of the material receiving the current frame. Unlike [Gif3d], the alpha mode of the material is left untouched.
+/// Like [Gif3d], the material is copied unless the entity has a [GifSharedMaterial].
+///
+/// ```ignore
+/// commands.spawn((
+///     Mesh3d(mesh),
+///     MeshMaterial3d(materials.add(StandardMaterial::default())),
+///     GifMaterial::new(asset_server.load("mask.gif"))
+///         .with_slot(|mat: &mut StandardMaterial, image| mat.emissive_texture = Some(image)),
+/// ));
+/// app.register_gif_target::<GifMaterial<MyMaterial>>();
+/// ```
+///
+/// [GifPlugin](crate::GifPlugin) only animates `GifMaterial<StandardMaterial>`, other materials need to be
+/// registered with [register_gif_target](crate::GifAppExt::register_gif_target).
+#[derive(Component, Debug, Clone)]
+#[require(MeshMaterial3d<M>, GifPlayer)]
+pub struct GifMaterial<M: Material> {
+    pub handle: Handle<GifAsset>,
+    /// Texture slots showing the current frame
+    pub slots: Vec<fn(&mut M, Handle<Image>)>,
+    /// In atlas mode, the slots receive the whole atlas, and this selects the current frame within it
+    pub uv_transform: Option<fn(&mut M, Affine2)>,
+}
+
+impl<M: Material> GifMaterial<M> {
+    /// A [GifMaterial] without any slot
+    pub fn new(handle: Handle<GifAsset>) -> Self {
+        Self {
+            handle,
+            slots: vec![],
+            uv_transform: None,
+        }
+    }
+
+    /// Show the current frame in another texture slot
+    pub fn with_slot(mut self, slot: fn(&mut M, Handle<Image>)) -> Self {
+        self.slots.push(slot);
+        self
+    }
+
+    /// Select the current frame within the atlas, in atlas mode
+    pub fn with_uv_transform(mut self, uv_transform: fn(&mut M, Affine2)) -> Self {
+        self.uv_transform = Some(uv_transform);
+        self
+    }
+}
+
+/// Insert this component next to a [Gif3d], a [Gif2dMesh] or a [GifMaterial] to animate its material in place,
+/// instead of a copy of it.
+///
+/// Every mesh using the material then shows the frames played by this entity. Those other meshes
+/// should only hold the [MeshMaterial3d] (or [MeshMaterial2d]), otherwise several players would drive the same material.
+///
+/// ```ignore
+/// let material = materials.add(StandardMaterial::default());
+/// commands.spawn((Mesh3d(flag.clone()), MeshMaterial3d(material.clone()), Gif3d { handle }, GifSharedMaterial));
+/// commands.spawn((Mesh3d(flag), MeshMaterial3d(material)));
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct GifSharedMaterial;
+
+/// Insert this component next to a gif to keep its progress when its handle changes,
+/// instead of starting the new GIF over: a GIF half-way through goes on half-way through the new one.
+///
+/// ```ignore
+/// commands.spawn((Gif { handle: walk }, GifKeepProgress));
+/// // later on
+/// gif.handle = run;
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct GifKeepProgress;
+
+/// Insert this component next to a gif to trigger a [GifFrameChanged](crate::GifFrameChanged)
+/// each time its displayed frame changes.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct GifEmitFrameChanged;
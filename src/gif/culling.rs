@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use crate::gif::target::GifTarget;
+
+/// Insert this component next to a gif to choose what happens to it while it is hidden,
+/// either with [Visibility::Hidden] or out of the view of the cameras.
+/// Without it, the [GifSettings::default_culling](crate::GifSettings::default_culling) is used.
+///
+/// ```ignore
+/// // hundreds of animated tiles, most of them off-screen
+/// commands.spawn((Gif { handle: water.clone() }, GifCulling::SkipRender));
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GifCulling {
+    /// Keep animating the gif
+    #[default]
+    AlwaysRun,
+    /// Stop the time of the gif: it goes on from the same frame once visible again
+    Freeze,
+    /// Keep the time of the gif going, but only update its texture once visible again,
+    /// so that it shows the frame it would have reached
+    SkipRender,
+}
+
+/// Whether the entity of a `T` is hidden, according to its visibility components
+pub(crate) fn is_hidden<T: GifTarget>(
+    inherited: Option<&InheritedVisibility>,
+    view: Option<&ViewVisibility>,
+) -> bool {
+    inherited.is_some_and(|inherited| !inherited.get())
+        || (T::VIEW_CULLED && view.is_some_and(|view| !view.get()))
+}
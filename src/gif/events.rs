@@ -0,0 +1,72 @@
+use bevy::ecs::{entity::Entity, event::EntityEvent};
+
+/// Triggered on a gif entity when a loop of its GIF is over, including the last one.
+///
+/// `loops_left` is the number of loops still to play, the one starting included.
+/// It is `None` for infinite GIFs, and `Some(0)` when the last loop is over.
+///
+/// After a long hitch, the gif skips whole loops rather than playing through thousands of frames
+/// in a single update: a single event stands for them, and the [GifFrameEvent]s of their frames
+/// are not triggered. It then counts `skipped + 1` loops.
+///
+/// ```ignore
+/// commands
+///     .spawn(Gif { handle })
+///     .observe(|looped: On<GifLoopCompleted>| info!("{:?} loops left", looped.loops_left));
+/// ```
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct GifLoopCompleted {
+    pub entity: Entity,
+    pub loops_left: Option<u16>,
+    /// Loops skipped before this one, 0 unless the gif fell far behind
+    pub skipped: u32,
+}
+
+/// Triggered on a gif entity when all the loops of its GIF are over.
+///
+/// It never happens for infinite GIFs.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct GifFinished {
+    pub entity: Entity,
+}
+
+/// Triggered on a gif entity when a frame with markers is played, once per marker.
+///
+/// Frames are not skipped when several of them go by in a single update, unless whole loops
+/// are skipped after a long hitch (see [GifLoopCompleted::skipped]).
+/// Seeking to a frame does not trigger its markers. See [GifAsset::markers](crate::GifAsset::markers).
+///
+/// ```ignore
+/// commands
+///     .spawn(Gif { handle: walk })
+///     .observe(|event: On<GifFrameEvent>| {
+///         if event.marker == "footstep" {
+///             info!("Step on frame {}", event.frame);
+///         }
+///     });
+/// ```
+#[derive(EntityEvent, Debug, Clone)]
+pub struct GifFrameEvent {
+    pub entity: Entity,
+    pub frame: usize,
+    pub marker: String,
+}
+
+/// Triggered on a gif entity with a [GifEmitFrameChanged](crate::GifEmitFrameChanged) when the frame its target displays changes.
+/// Frames which were never displayed are not reported: the ones going by within a single update,
+/// or while the gif is hidden (see [GifCulling::SkipRender](crate::GifCulling::SkipRender)) or capped by
+/// [GifAccessibility::max_fps](crate::GifAccessibility::max_fps).
+///
+/// ```ignore
+/// commands
+///     .spawn((Gif { handle }, GifEmitFrameChanged))
+///     .observe(|changed: On<GifFrameChanged>| info!("{} -> {}", changed.previous, changed.current));
+/// ```
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub struct GifFrameChanged {
+    pub entity: Entity,
+    pub previous: usize,
+    pub current: usize,
+    /// Loops completed since the GIF started, see [GifPlayer::loop_index](crate::GifPlayer::loop_index)
+    pub loop_index: u32,
+}
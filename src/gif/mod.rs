@@ -1,10 +1,29 @@
+mod accessibility;
+mod animator;
+mod clock;
 mod components;
-mod messages;
+mod culling;
+mod events;
+mod offset;
 mod plugin;
+mod sync;
 mod systems;
-
-pub use components::{Gif, Gif3d, GifAsset, GifDespawn, GifNode, GifPlayer};
-pub use plugin::GifPlugin;
+mod target;
+
+pub use accessibility::{GifAccessibility, GifEssential};
+pub use animator::{GifAnimator, GifCondition, GifState, GifTransition, GifTransitionTiming};
+pub use clock::GifClock;
+pub use components::{
+    Gif, Gif2dMesh, Gif3d, GifAsset, GifAtlas, GifDespawn, GifDirection, GifEmitFrameChanged,
+    GifKeepProgress, GifLoaderSettings, GifMaterial, GifNode, GifOnFinish, GifPlayer, GifRepeat,
+    GifSharedMaterial,
+};
+pub use culling::GifCulling;
+pub use events::{GifFinished, GifFrameChanged, GifFrameEvent, GifLoopCompleted};
+pub use offset::{GifRng, GifStartOffset};
+pub use plugin::{GifAppExt, GifPlugin, GifSettings, GifSystems};
+pub use sync::GifSyncGroup;
+pub use target::GifTarget;
 
 /// This was generated by Github Copilot with GPT-5 mini model.
 /// I resorted to using AI for tests because I was struggling myself,
@@ -15,10 +34,18 @@ mod tests {
     use std::time::Duration;
 
     use crate::{
-        gif::{components::GifLoader, messages::GifDespawnMessage, systems::*},
+        gif::{
+            clock::GifClockDeltas,
+            components::{GifLoader, atlas_grid, comment_markers},
+            sync::GifSyncGroups,
+            systems::*,
+        },
         *,
     };
-    use bevy::prelude::*;
+    use bevy::{
+        asset::RenderAssetUsages, image::ImageSampler, prelude::*,
+        render::render_resource::TextureFormat, time::TimeUpdateStrategy,
+    };
 
     /// Build a minimal app for testing purposes
     fn build_app() -> App {
@@ -30,11 +57,67 @@ mod tests {
         // Ensure Image and GifAsset storage exist and the gif loader is registered
         app.init_asset::<Image>();
         app.init_asset::<StandardMaterial>();
+        app.init_asset::<ColorMaterial>();
+        app.init_asset::<TextureAtlasLayout>();
         app.init_asset::<GifAsset>();
         app.init_asset_loader::<GifLoader>();
+        // Read by the gif systems, initialized when a target is registered
+        app.init_resource::<GifClockDeltas>();
+        app.init_resource::<GifSettings>();
+        app.init_resource::<GifAccessibility>();
+        app.init_resource::<GifSyncGroups>();
+        app.insert_resource(GifRng::seeded(0));
         app
     }
 
+    /// Events triggered on gif entities, recorded by [record_events]
+    #[derive(Resource, Default)]
+    struct GifEvents {
+        loops: Vec<(Entity, Option<u16>)>,
+        skipped: u32,
+        finished: Vec<Entity>,
+        markers: Vec<(Entity, usize, String)>,
+        frames: Vec<(Entity, usize, usize, u32)>,
+    }
+
+    /// Record the events triggered on gif entities in the [GifEvents] resource
+    fn record_events(app: &mut App) {
+        app.init_resource::<GifEvents>();
+        app.add_observer(
+            |looped: On<GifLoopCompleted>, mut events: ResMut<GifEvents>| {
+                events.loops.push((looped.entity, looped.loops_left));
+                events.skipped += looped.skipped;
+            },
+        );
+        app.add_observer(|finished: On<GifFinished>, mut events: ResMut<GifEvents>| {
+            events.finished.push(finished.entity);
+        });
+        app.add_observer(|frame: On<GifFrameEvent>, mut events: ResMut<GifEvents>| {
+            events
+                .markers
+                .push((frame.entity, frame.frame, frame.marker.clone()));
+        });
+        app.add_observer(
+            |changed: On<GifFrameChanged>, mut events: ResMut<GifEvents>| {
+                events.frames.push((
+                    changed.entity,
+                    changed.previous,
+                    changed.current,
+                    changed.loop_index,
+                ));
+            },
+        );
+    }
+
+    /// Make each update advance the time by `delta`, instead of the real elapsed time
+    fn set_delta(app: &mut App, delta: Duration) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(delta));
+        // virtual time is clamped to 250ms by default
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(10));
+    }
+
     /// Update the app a few times until the [GifAsset] is properly loaded by the [AssetServer],
     /// or panic!
     fn wait_for_asset(app: &mut App, handle: &Handle<GifAsset>) {
@@ -42,10 +125,10 @@ mod tests {
         let mut tries = 0;
         loop {
             app.update();
-            if let Some(gif_asset) = app.world().resource::<Assets<GifAsset>>().get(handle) {
-                if !gif_asset.frames.is_empty() {
-                    break;
-                }
+            if let Some(gif_asset) = app.world().resource::<Assets<GifAsset>>().get(handle)
+                && !gif_asset.frames.is_empty()
+            {
+                break;
             }
             tries += 1;
             if tries > MAX_TRIES {
@@ -54,12 +137,198 @@ mod tests {
         }
     }
 
+    /// Palette used by the hand-built GIFs: transparent, red, green, blue
+    const PALETTE: [u8; 12] = [0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+
+    /// A frame of a hand-built GIF, covering only a sub-rectangle of the logical screen
+    struct TestFrame {
+        left: u16,
+        top: u16,
+        width: u16,
+        height: u16,
+        index: u8,
+        dispose: ::gif::DisposalMethod,
+    }
+
+    /// Encode an optimized GIF of `size`x`size` pixels, where each frame is filled with a
+    /// single palette index. Index 0 is transparent.
+    fn encode_gif(size: u16, frames: &[TestFrame]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = ::gif::Encoder::new(&mut bytes, size, size, &PALETTE).unwrap();
+            for frame in frames {
+                let pixels = vec![frame.index; frame.width as usize * frame.height as usize];
+                encoder
+                    .write_frame(&::gif::Frame {
+                        delay: 10,
+                        dispose: frame.dispose,
+                        transparent: Some(0),
+                        left: frame.left,
+                        top: frame.top,
+                        width: frame.width,
+                        height: frame.height,
+                        buffer: pixels.into(),
+                        ..Default::default()
+                    })
+                    .unwrap();
+            }
+        }
+        bytes
+    }
+
+    /// Read the pixel at (x, y) of a decoded frame
+    fn pixel(frame: &crate::gif::components::GifFrame, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * frame.width as usize + x) * 4;
+        frame.rgba[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_composite_keep() {
+        let bytes = encode_gif(
+            4,
+            &[
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 4,
+                    height: 4,
+                    index: 1,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+                TestFrame {
+                    left: 1,
+                    top: 1,
+                    width: 2,
+                    height: 2,
+                    index: 2,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+            ],
+        );
+        let (frames, _) = GifLoader::decode(&bytes).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        for frame in frames.iter() {
+            assert_eq!((frame.width, frame.height), (4, 4));
+            assert_eq!(frame.rgba.len(), 4 * 4 * 4);
+        }
+        // The second frame is drawn over the first one, which is kept around it
+        assert_eq!(pixel(&frames[1], 0, 0), RED);
+        assert_eq!(pixel(&frames[1], 1, 1), GREEN);
+        assert_eq!(pixel(&frames[1], 2, 2), GREEN);
+        assert_eq!(pixel(&frames[1], 3, 3), RED);
+    }
+
+    #[test]
+    fn test_composite_background() {
+        let bytes = encode_gif(
+            4,
+            &[
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 4,
+                    height: 4,
+                    index: 1,
+                    dispose: ::gif::DisposalMethod::Background,
+                },
+                TestFrame {
+                    left: 2,
+                    top: 2,
+                    width: 2,
+                    height: 2,
+                    index: 2,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+            ],
+        );
+        let (frames, _) = GifLoader::decode(&bytes).unwrap();
+
+        assert_eq!(pixel(&frames[0], 0, 0), RED);
+        // The first frame was cleared before drawing the second one
+        assert_eq!(pixel(&frames[1], 0, 0), TRANSPARENT);
+        assert_eq!(pixel(&frames[1], 3, 3), GREEN);
+    }
+
+    #[test]
+    fn test_composite_previous() {
+        let bytes = encode_gif(
+            4,
+            &[
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 4,
+                    height: 4,
+                    index: 1,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 2,
+                    height: 2,
+                    index: 2,
+                    dispose: ::gif::DisposalMethod::Previous,
+                },
+                TestFrame {
+                    left: 3,
+                    top: 3,
+                    width: 1,
+                    height: 1,
+                    index: 3,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+            ],
+        );
+        let (frames, _) = GifLoader::decode(&bytes).unwrap();
+
+        assert_eq!(pixel(&frames[1], 0, 0), GREEN);
+        // The second frame was undone before drawing the third one
+        assert_eq!(pixel(&frames[2], 0, 0), RED);
+        assert_eq!(pixel(&frames[2], 3, 3), BLUE);
+    }
+
+    #[test]
+    fn test_composite_transparency() {
+        let bytes = encode_gif(
+            4,
+            &[
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 4,
+                    height: 4,
+                    index: 1,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+                TestFrame {
+                    left: 0,
+                    top: 0,
+                    width: 4,
+                    height: 4,
+                    index: 0,
+                    dispose: ::gif::DisposalMethod::Keep,
+                },
+            ],
+        );
+        let (frames, _) = GifLoader::decode(&bytes).unwrap();
+
+        // A fully transparent frame shows what is underneath
+        assert_eq!(pixel(&frames[1], 0, 0), RED);
+        assert_eq!(pixel(&frames[1], 3, 3), RED);
+    }
+
     #[test]
     fn test_gif_init() {
         let mut app = build_app();
 
         // Add the system under test
-        app.add_systems(Update, initialize_gifs);
+        app.add_systems(Update, initialize_gifs::<Gif>);
 
         // Load the gif asset via the AssetServer so the loader creates a GifAsset
         let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
@@ -84,12 +353,12 @@ mod tests {
         // Run one update to let initialize_gifs run (it matches Added<Gif>)
         app.update();
 
-        // Retrieve the GifAsset and check it was initialized (handles created)
+        // Retrieve the GifAsset and check it was loaded (handles created)
         let assets = app.world().resource::<Assets<GifAsset>>();
         let gif_asset = assets.get(&handle).expect("GifAsset should be present");
         assert!(
             !gif_asset.handles.is_empty(),
-            "initialize_gifs should have added Image handles"
+            "the loader should have added Image handles"
         );
         assert_eq!(
             gif_asset.handles.len(),
@@ -106,7 +375,7 @@ mod tests {
             .get::<GifPlayer>(entity)
             .expect("GifPlayer component should exist");
 
-        // The sprite.image should be the first frame handle created by initialize_gifs
+        // The sprite.image should be the first frame handle created by the loader
         let first_handle = gif_asset.handles.first().unwrap();
         assert_eq!(&sprite.image, first_handle);
 
@@ -117,10 +386,189 @@ mod tests {
         assert_eq!(player.remaining, gif_asset.times);
     }
 
+    #[test]
+    fn test_labeled_frames() {
+        let mut app = build_app();
+
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        assert!(
+            app.world()
+                .resource::<AssetServer>()
+                .is_loaded_with_dependencies(&handle)
+        );
+
+        // Frames are sub-assets of the gif
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap();
+        let images = app.world().resource::<Assets<Image>>();
+        for handle in gif_asset.handles.iter() {
+            assert!(
+                images.contains(handle),
+                "frames should be loaded with the gif"
+            );
+        }
+        let path = gif_asset.handles[3]
+            .path()
+            .expect("frames should have a path");
+        assert_eq!(path.label(), Some("frame3"));
+
+        // And can be loaded directly as still images
+        let frame: Handle<Image> = app.world().load_asset("frog_five.gif#frame3");
+        assert_eq!(frame, gif_asset.handles[3]);
+    }
+
+    #[test]
+    fn test_loader_settings() {
+        let mut app = build_app();
+        app.add_systems(Update, initialize_gifs::<Gif>);
+
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.sampler = ImageSampler::nearest();
+                settings.is_srgb = false;
+                settings.repeat = Some(GifRepeat::Infinite);
+                settings.min_frame_delay = Duration::from_millis(500);
+                settings.frames = Some(1..3);
+            },
+        );
+        wait_for_asset(&mut app, &handle);
+
+        {
+            let assets = app.world().resource::<Assets<GifAsset>>();
+            let gif_asset = assets.get(&handle).expect("GifAsset should be present");
+            assert_eq!(gif_asset.frames.len(), 2); // only the 2nd and 3rd frames
+            assert_eq!(gif_asset.times, None); // 5 loops overridden to infinite
+            for frame in gif_asset.frames.iter() {
+                assert_eq!(frame.duration, Duration::from_millis(500)); // 200ms stretched
+            }
+        }
+
+        app.world_mut().spawn(Gif {
+            handle: handle.clone(),
+        });
+        app.update();
+
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap();
+        let image = app
+            .world()
+            .resource::<Assets<Image>>()
+            .get(&gif_asset.handles[0])
+            .expect("frame images should be created");
+        assert_eq!(image.sampler, ImageSampler::nearest());
+        assert_eq!(image.texture_descriptor.format, TextureFormat::Rgba8Unorm);
+    }
+
+    #[test]
+    fn test_drop_frame_data() {
+        let mut app = build_app();
+
+        let handle: Handle<GifAsset> = app
+            .world()
+            .resource::<AssetServer>()
+            .load_with_settings("frog_five.gif", |settings: &mut GifLoaderSettings| {
+                settings.keep_frame_data = false
+            });
+        wait_for_asset(&mut app, &handle);
+
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap();
+        let images = app.world().resource::<Assets<Image>>();
+        for (frame, handle) in gif_asset.frames.iter().zip(gif_asset.handles.iter()) {
+            // dimensions and timings are still known
+            assert!(frame.width > 0 && frame.height > 0);
+            assert!(frame.duration >= Duration::from_millis(200));
+            // but pixels only live in the render world
+            assert!(frame.rgba.is_empty());
+            let image = images.get(handle).unwrap();
+            assert_eq!(image.asset_usage, RenderAssetUsages::RENDER_WORLD);
+        }
+    }
+
+    #[test]
+    fn test_atlas_mode() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        let handle: Handle<GifAsset> = app
+            .world()
+            .resource::<AssetServer>()
+            .load_with_settings("frog_five.gif", |settings: &mut GifLoaderSettings| {
+                settings.atlas = true
+            });
+        wait_for_asset(&mut app, &handle);
+
+        let entity = app
+            .world_mut()
+            .spawn(Gif {
+                handle: handle.clone(),
+            })
+            .id();
+        app.update();
+
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap();
+        assert!(
+            gif_asset.handles.is_empty(),
+            "no image per frame in atlas mode"
+        );
+        let atlas = gif_asset.atlas.clone().expect("atlas should be created");
+        assert_eq!((atlas.columns, atlas.rows), (2, 2)); // 4 frames
+        let first_frame = gif_asset.frames[0].clone();
+
+        let sprite = app.world().get::<Sprite>(entity).unwrap();
+        assert_eq!(sprite.image, atlas.image);
+        let texture_atlas = sprite
+            .texture_atlas
+            .as_ref()
+            .expect("sprite should use the atlas");
+        assert_eq!(texture_atlas.layout, atlas.layout);
+        assert_eq!(texture_atlas.index, 0);
+
+        // go to the next frame
+        {
+            let mut player = app.world_mut().get_mut::<GifPlayer>(entity).unwrap();
+            player.timer.reset();
+            player
+                .timer
+                .tick(first_frame.duration - Duration::from_nanos(1));
+        }
+        app.update();
+
+        let sprite = app.world().get::<Sprite>(entity).unwrap();
+        assert_eq!(sprite.image, atlas.image); // same texture
+        assert_eq!(sprite.texture_atlas.as_ref().unwrap().index, 1);
+
+        let image = app
+            .world()
+            .resource::<Assets<Image>>()
+            .get(&atlas.image)
+            .unwrap();
+        assert_eq!(image.width(), first_frame.width * 2);
+        assert_eq!(image.height(), first_frame.height * 2);
+    }
+
     #[test]
     fn test_custom_params_spawn() {
         let mut app = build_app();
-        app.add_systems(Update, initialize_gifs);
+        app.add_systems(Update, initialize_gifs::<Gif>);
 
         let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
         wait_for_asset(&mut app, &handle);
@@ -155,7 +603,7 @@ mod tests {
         let gif_asset = assets.get(&handle).expect("GifAsset should be present");
         assert!(
             !gif_asset.handles.is_empty(),
-            "initialize_gifs should create Image handles"
+            "the loader should create Image handles"
         );
 
         let first_handle = gif_asset.handles.first().unwrap();
@@ -183,9 +631,8 @@ mod tests {
     #[test]
     fn test_sprite_change_on_timer_tick() {
         let mut app = build_app();
-        app.add_systems(Update, initialize_gifs);
-        app.add_systems(Update, animate_gifs);
-        app.add_message::<GifDespawnMessage>();
+        app.add_systems(Update, initialize_gifs::<Gif>);
+        app.add_systems(Update, animate_gifs::<Gif>);
 
         let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
         wait_for_asset(&mut app, &handle);
@@ -264,9 +711,8 @@ mod tests {
     #[test]
     fn test_nth_loading_and_updating() {
         let mut app = build_app();
-        app.add_systems(Update, initialize_gifs);
-        app.add_systems(Update, animate_gifs);
-        app.add_message::<GifDespawnMessage>();
+        app.add_systems(Update, initialize_gifs::<Gif>);
+        app.add_systems(Update, animate_gifs::<Gif>);
 
         let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
         wait_for_asset(&mut app, &handle);
@@ -319,9 +765,11 @@ mod tests {
                 let mut player = query
                     .single_mut(app.world_mut())
                     .expect("Should be exactly one player");
+                // the time carried over from the previous frame is already elapsed
+                let elapsed = player.timer.elapsed();
                 player
                     .timer
-                    .tick(Duration::from_millis(*time) - Duration::from_nanos(1));
+                    .tick(Duration::from_millis(*time) - elapsed - Duration::from_nanos(1));
                 app.update();
             }
         }
@@ -339,9 +787,8 @@ mod tests {
     #[test]
     fn test_nth_stop_playing() {
         let mut app = build_app();
-        app.add_systems(Update, initialize_gifs);
-        app.add_systems(Update, animate_gifs);
-        app.add_message::<GifDespawnMessage>();
+        app.add_systems(Update, initialize_gifs::<Gif>);
+        app.add_systems(Update, animate_gifs::<Gif>);
 
         let handle: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
         wait_for_asset(&mut app, &handle);
@@ -376,9 +823,11 @@ mod tests {
                 let mut player = query
                     .single_mut(app.world_mut())
                     .expect("Should be exactly one player");
+                // the time carried over from the previous frame is already elapsed
+                let elapsed = player.timer.elapsed();
                 player
                     .timer
-                    .tick(Duration::from_millis(*time) - Duration::from_nanos(1));
+                    .tick(Duration::from_millis(*time) - elapsed - Duration::from_nanos(1));
                 app.update();
             }
         }
@@ -396,9 +845,8 @@ mod tests {
     #[test]
     fn test_repeating_never_stops() {
         let mut app = build_app();
-        app.add_systems(Update, initialize_gifs);
-        app.add_systems(Update, animate_gifs);
-        app.add_message::<GifDespawnMessage>();
+        app.add_systems(Update, initialize_gifs::<Gif>);
+        app.add_systems(Update, animate_gifs::<Gif>);
 
         let handle: Handle<GifAsset> = app.world().load_asset("frog_infinite.gif");
         wait_for_asset(&mut app, &handle);
@@ -434,9 +882,11 @@ mod tests {
                     let mut player = query
                         .single_mut(app.world_mut())
                         .expect("Should be exactly one player");
+                    // the time carried over from the previous frame is already elapsed
+                    let elapsed = player.timer.elapsed();
                     player
                         .timer
-                        .tick(Duration::from_millis(*time) - Duration::from_nanos(1));
+                        .tick(Duration::from_millis(*time) - elapsed - Duration::from_nanos(1));
                     app.update();
                 }
             }
@@ -445,9 +895,11 @@ mod tests {
                 let mut player = query
                     .single_mut(app.world_mut())
                     .expect("Should be exactly one player");
+                // the time carried over from the previous frame is already elapsed
+                let elapsed = player.timer.elapsed();
                 player
                     .timer
-                    .tick(Duration::from_millis(*time) - Duration::from_nanos(1));
+                    .tick(Duration::from_millis(*time) - elapsed - Duration::from_nanos(1));
                 app.update();
             }
         }
@@ -468,8 +920,8 @@ mod tests {
         let mut app = build_app();
 
         // Add the system under test
-        app.add_systems(Update, (initialize_gifs, animate_gifs, despawn_gifs));
-        app.add_message::<GifDespawnMessage>();
+        app.add_systems(Update, (initialize_gifs::<Gif>, animate_gifs::<Gif>));
+        app.add_observer(finish_gifs::<Gif>);
 
         // Load the gif asset via the AssetServer so the loader creates a GifAsset
         let handle: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
@@ -509,9 +961,11 @@ mod tests {
                 let mut player = query
                     .single_mut(app.world_mut())
                     .expect("Should be exactly one player");
+                // the time carried over from the previous frame is already elapsed
+                let elapsed = player.timer.elapsed();
                 player
                     .timer
-                    .tick(Duration::from_millis(*time) - Duration::from_nanos(1));
+                    .tick(Duration::from_millis(*time) - elapsed - Duration::from_nanos(1));
                 app.update();
             }
         }
@@ -519,4 +973,1578 @@ mod tests {
         let mut query = app.world_mut().query::<&Gif>();
         assert_eq!(0, query.iter(app.world()).count());
     }
+
+    #[test]
+    fn test_playback_controls() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(100));
+
+        let entity = app
+            .world_mut()
+            .spawn(Gif {
+                handle: handle.clone(),
+            })
+            .id();
+        app.update();
+
+        let current = |app: &App| app.world().get::<GifPlayer>(entity).unwrap().current;
+        let image = |app: &App| app.world().get::<Sprite>(entity).unwrap().image.clone();
+        let frame = |app: &App, index: usize| {
+            app.world()
+                .resource::<Assets<GifAsset>>()
+                .get(&handle)
+                .unwrap()
+                .handles[index]
+                .clone()
+        };
+        fn player(app: &mut App, entity: Entity) -> Mut<'_, GifPlayer> {
+            app.world_mut().get_mut::<GifPlayer>(entity).unwrap()
+        }
+
+        // paused: time flies, but not for the gif
+        player(&mut app, entity).pause();
+        for _ in 0..20 {
+            app.update();
+        }
+        assert!(player(&mut app, entity).is_paused());
+        assert_eq!(current(&app), 0);
+
+        // 10 times faster, the first frame is over in a single update
+        player(&mut app, entity).resume();
+        player(&mut app, entity).set_speed(10.);
+        app.update();
+        assert_eq!(current(&app), 1);
+        assert_eq!(image(&app), frame(&app, 1));
+
+        // seeking shows the frame right away
+        player(&mut app, entity).set_speed(1.);
+        player(&mut app, entity).seek_frame(3);
+        app.update();
+        assert_eq!(current(&app), 3);
+        assert_eq!(image(&app), frame(&app, 3));
+
+        player(&mut app, entity).pause();
+        player(&mut app, entity).seek_time(Duration::from_millis(1300));
+        app.update();
+        assert_eq!(current(&app), 2);
+        assert_eq!(image(&app), frame(&app, 2));
+        assert_eq!(
+            app.world()
+                .get::<GifPlayer>(entity)
+                .unwrap()
+                .timer
+                .elapsed(),
+            Duration::from_millis(100)
+        );
+
+        player(&mut app, entity).restart();
+        app.update();
+        assert_eq!(current(&app), 0);
+        assert_eq!(image(&app), frame(&app, 0));
+        assert_eq!(
+            app.world().get::<GifPlayer>(entity).unwrap().remaining,
+            Some(4)
+        );
+
+        player(&mut app, entity).set_speed(-1.);
+        assert_eq!(player(&mut app, entity).speed(), 0.);
+        player(&mut app, entity).set_speed(2.);
+        player(&mut app, entity).set_speed(f32::INFINITY);
+        player(&mut app, entity).set_speed(f32::NAN);
+        assert_eq!(player(&mut app, entity).speed(), 2.);
+
+        // the global speed freezes the gifs when it makes no sense
+        player(&mut app, entity).resume();
+        for speed in [-1., f32::INFINITY, f32::NAN] {
+            app.world_mut().resource_mut::<GifSettings>().speed = speed;
+            app.update();
+            assert_eq!(current(&app), 0);
+        }
+        // and saturates when it is too large
+        app.world_mut().resource_mut::<GifSettings>().speed = f32::MAX;
+        app.update();
+    }
+
+    /// Play frog_five.gif in the given direction, with frames all lasting as long as an update,
+    /// and return the frames shown and the remaining loops after each update.
+    fn play_frames(direction: GifDirection, updates: usize) -> Vec<(usize, Option<u16>)> {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.min_frame_delay = Duration::from_millis(1000);
+            },
+        );
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Gif { handle },
+                GifPlayer::default().with_direction(direction),
+            ))
+            .id();
+        (0..updates)
+            .map(|_| {
+                app.update();
+                let player = app.world().get::<GifPlayer>(entity).unwrap();
+                (player.current, player.remaining)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reverse_playback() {
+        // first frame is shown then left within the first update
+        let frames = play_frames(GifDirection::Reverse, 5);
+        assert_eq!(
+            frames,
+            vec![
+                (2, Some(4)),
+                (1, Some(4)),
+                (0, Some(4)),
+                (3, Some(3)),
+                (2, Some(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ping_pong_playback() {
+        let frames = play_frames(GifDirection::PingPong, 8);
+        assert_eq!(
+            frames
+                .iter()
+                .map(|(current, _)| *current)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 2, 1, 0, 1, 2]
+        );
+        // a loop is over when back on the first frame
+        assert_eq!(frames[4].1, Some(4));
+        assert_eq!(frames[5].1, Some(3));
+    }
+
+    #[test]
+    fn test_carry_over_elapsed_time() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entity = app.world_mut().spawn(Gif { handle }).id();
+        app.update();
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.current, 1);
+        assert_eq!(player.timer.elapsed(), Duration::ZERO);
+
+        // 1000ms go through the 3 short frames, end the loop, and are 400ms into the first frame
+        app.update();
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.current, 0);
+        assert_eq!(player.remaining, Some(3));
+        assert_eq!(player.timer.elapsed(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_several_loops_in_one_update() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        // 5 loops of 1600ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(5000));
+        record_events(&mut app);
+
+        let entity = app.world_mut().spawn(Gif { handle }).id();
+        app.update();
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.remaining, Some(1)); // 3 loops and 200ms
+        let events = app.world().resource::<GifEvents>();
+        assert_eq!(
+            events.loops,
+            vec![(entity, Some(4)), (entity, Some(3)), (entity, Some(2))]
+        );
+        assert!(events.finished.is_empty());
+
+        app.update();
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.remaining, Some(0));
+        assert_eq!(player.current, 0);
+        assert!(player.timer.is_paused(), "all loops are over");
+        let events = app.world().resource::<GifEvents>();
+        assert_eq!(events.loops[3..], [(entity, Some(1)), (entity, Some(0))]);
+        assert_eq!(events.finished, vec![entity]);
+    }
+
+    #[test]
+    fn test_despawn_only_finished_entity() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+        app.add_observer(finish_gifs::<Gif>);
+
+        // a single loop of 1600ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(500));
+
+        let first = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                GifDespawn,
+            ))
+            .id();
+        app.update();
+        app.update();
+        let second = app.world_mut().spawn((Gif { handle }, GifDespawn)).id();
+        app.update();
+        assert!(app.world().get_entity(first).is_ok());
+
+        // the first gif is over, the second one which uses the same asset is not
+        app.update();
+        assert!(app.world().get_entity(first).is_err());
+        assert!(app.world().get_entity(second).is_ok());
+    }
+
+    #[test]
+    fn test_on_finish() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+        app.add_observer(finish_gifs::<Gif>);
+
+        // a single loop of 1600ms, done within the first update
+        let once: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        let five: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &once);
+        wait_for_asset(&mut app, &five);
+        set_delta(&mut app, Duration::from_millis(2000));
+
+        let mut spawn = |on_finish: GifOnFinish| {
+            app.world_mut()
+                .spawn((
+                    Gif {
+                        handle: once.clone(),
+                    },
+                    on_finish,
+                ))
+                .id()
+        };
+        let despawned = spawn(GifOnFinish::Despawn);
+        let removed = spawn(GifOnFinish::Remove);
+        let hidden = spawn(GifOnFinish::Hide);
+        let held = spawn(GifOnFinish::HoldLastFrame);
+        let reset = spawn(GifOnFinish::ResetToFirstFrame);
+        let switched = spawn(GifOnFinish::Switch(five.clone()));
+        let child = app.world_mut().spawn(ChildOf(despawned)).id();
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&once)
+            .unwrap()
+            .handles;
+
+        assert!(world.get_entity(despawned).is_err());
+        assert!(
+            world.get_entity(child).is_err(),
+            "children are despawned too"
+        );
+
+        assert!(world.get::<Gif>(removed).is_none());
+        assert!(world.get::<GifPlayer>(removed).is_none());
+        assert_eq!(world.get::<Sprite>(removed).unwrap().image, frames[3]);
+
+        assert_eq!(world.get::<Visibility>(hidden), Some(&Visibility::Hidden));
+
+        assert_eq!(world.get::<GifPlayer>(held).unwrap().current, 3);
+        assert_eq!(world.get::<Sprite>(held).unwrap().image, frames[3]);
+
+        assert_eq!(world.get::<GifPlayer>(reset).unwrap().current, 0);
+        assert_eq!(world.get::<Sprite>(reset).unwrap().image, frames[0]);
+
+        assert_eq!(world.get::<Gif>(switched).unwrap().handle, five);
+        set_delta(&mut app, Duration::from_millis(100));
+        app.update();
+        let world = app.world();
+        let player = world.get::<GifPlayer>(switched).unwrap();
+        assert!(!player.timer.is_paused(), "the new gif is playing");
+        assert_eq!(player.remaining, Some(4));
+    }
+
+    /// A custom target, which records the frames it is shown
+    #[derive(Component)]
+    #[require(GifPlayer, ShownFrames)]
+    struct RecordedGif {
+        handle: Handle<GifAsset>,
+    }
+
+    #[derive(Component, Default)]
+    struct ShownFrames(Vec<usize>);
+
+    impl GifTarget for RecordedGif {
+        type Data = &'static mut ShownFrames;
+        type Param = ();
+
+        fn handle(&self) -> &Handle<GifAsset> {
+            &self.handle
+        }
+
+        fn set_handle(&mut self, handle: Handle<GifAsset>) {
+            self.handle = handle;
+        }
+
+        fn show_frame(
+            &self,
+            shown: &mut Mut<'_, ShownFrames>,
+            _: &mut (),
+            _: &GifAsset,
+            index: usize,
+        ) {
+            shown.0.push(index);
+        }
+    }
+
+    #[test]
+    fn test_custom_target() {
+        let mut app = build_app();
+        app.register_gif_target::<RecordedGif>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entity = app.world_mut().spawn(RecordedGif { handle }).id();
+        app.update();
+        // a frame is shown at most once per update
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<ShownFrames>(entity).unwrap().0, vec![0, 1, 0]);
+        assert!(world.get::<Sprite>(entity).is_none());
+    }
+
+    #[test]
+    fn test_gif_material_slots() {
+        let mut app = build_app();
+        app.register_gif_target::<GifMaterial<StandardMaterial>>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                alpha_mode: AlphaMode::Mask(0.5),
+                ..default()
+            });
+        app.world_mut().spawn((
+            MeshMaterial3d(material.clone()),
+            GifMaterial::new(handle.clone())
+                .with_slot(|mat: &mut StandardMaterial, image| mat.emissive_texture = Some(image))
+                .with_slot(|mat, image| mat.occlusion_texture = Some(image)),
+            GifSharedMaterial,
+        ));
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .handles;
+        let mat = world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap();
+        assert_eq!(mat.emissive_texture.as_ref(), Some(&frames[1]));
+        assert_eq!(mat.occlusion_texture.as_ref(), Some(&frames[1]));
+        assert!(mat.base_color_texture.is_none());
+        assert!(matches!(mat.alpha_mode, AlphaMode::Mask(_)));
+    }
+
+    #[test]
+    fn test_material_copies() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif3d>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let mut spawn = |player: GifPlayer| {
+            app.world_mut()
+                .spawn((
+                    MeshMaterial3d(material.clone()),
+                    Gif3d {
+                        handle: handle.clone(),
+                    },
+                    player,
+                ))
+                .id()
+        };
+        let first = spawn(GifPlayer::default());
+        let mut player = GifPlayer::default();
+        player.pause();
+        let second = spawn(player);
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .handles;
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        // each entity plays its own copy of the material
+        let first_material = &world
+            .get::<MeshMaterial3d<StandardMaterial>>(first)
+            .unwrap()
+            .0;
+        let second_material = &world
+            .get::<MeshMaterial3d<StandardMaterial>>(second)
+            .unwrap()
+            .0;
+        assert_ne!(first_material, &material);
+        assert_ne!(second_material, &material);
+        assert_eq!(
+            materials
+                .get(first_material)
+                .unwrap()
+                .base_color_texture
+                .as_ref(),
+            Some(&frames[1])
+        );
+        assert_eq!(
+            materials
+                .get(second_material)
+                .unwrap()
+                .base_color_texture
+                .as_ref(),
+            Some(&frames[0])
+        );
+        assert!(
+            materials
+                .get(&material)
+                .unwrap()
+                .base_color_texture
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_shared_material() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif3d>();
+
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let driver = app
+            .world_mut()
+            .spawn((
+                MeshMaterial3d(material.clone()),
+                Gif3d {
+                    handle: handle.clone(),
+                },
+                GifSharedMaterial,
+            ))
+            .id();
+        let follower = app.world_mut().spawn(MeshMaterial3d(material.clone())).id();
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .handles;
+        for entity in [driver, follower] {
+            let mesh_material = world
+                .get::<MeshMaterial3d<StandardMaterial>>(entity)
+                .unwrap();
+            assert_eq!(mesh_material.0, material);
+        }
+        let mat = world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap();
+        assert_eq!(mat.base_color_texture.as_ref(), Some(&frames[1]));
+        assert!(matches!(mat.alpha_mode, AlphaMode::Blend));
+    }
+
+    #[test]
+    fn test_gif_2d_mesh() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif2dMesh>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(Color::WHITE);
+        let entity = app
+            .world_mut()
+            .spawn((
+                MeshMaterial2d(material.clone()),
+                Gif2dMesh {
+                    handle: handle.clone(),
+                },
+            ))
+            .id();
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .handles;
+        let materials = world.resource::<Assets<ColorMaterial>>();
+        let own_material = &world
+            .get::<MeshMaterial2d<ColorMaterial>>(entity)
+            .unwrap()
+            .0;
+        assert_ne!(own_material, &material);
+        assert_eq!(
+            materials.get(own_material).unwrap().texture.as_ref(),
+            Some(&frames[1])
+        );
+        assert!(materials.get(&material).unwrap().texture.is_none());
+    }
+
+    #[test]
+    fn test_clips() {
+        let mut app = build_app();
+        app.add_systems(
+            Update,
+            (initialize_gifs::<Gif>, animate_gifs::<Gif>).chain(),
+        );
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.repeat = Some(GifRepeat::Infinite);
+                settings.clips.insert("hop".to_string(), 1..4);
+            },
+        );
+        wait_for_asset(&mut app, &handle);
+        app.world_mut()
+            .resource_mut::<Assets<GifAsset>>()
+            .get_mut_untracked(&handle)
+            .unwrap()
+            .add_clip("sit", 0..1);
+        set_delta(&mut app, Duration::from_millis(200));
+
+        let entity = app
+            .world_mut()
+            .spawn((Gif { handle }, GifPlayer::default().with_clip("hop")))
+            .id();
+        fn current(app: &mut App, entity: Entity) -> usize {
+            app.update();
+            app.world().get::<GifPlayer>(entity).unwrap().current
+        }
+        // the clip loops over its own frames
+        assert_eq!([(); 4].map(|_| current(&mut app, entity)), [2, 3, 1, 2]);
+
+        let mut player = app.world_mut().get_mut::<GifPlayer>(entity).unwrap();
+        player.play_clip("sit");
+        assert_eq!(current(&mut app, entity), 0);
+        assert_eq!(current(&mut app, entity), 0);
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.clip(), Some("sit"));
+
+        let mut player = app.world_mut().get_mut::<GifPlayer>(entity).unwrap();
+        player.stop_clip();
+        player.set_direction(GifDirection::Reverse);
+        // starts over from the last frame of the whole GIF, which lasts 200ms
+        assert_eq!(current(&mut app, entity), 2);
+        assert_eq!(app.world().get::<GifPlayer>(entity).unwrap().clip(), None);
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1400));
+        let entities = [None, Some(GifSyncGroup(0))].map(|group| {
+            let mut entity = app.world_mut().spawn(Gif {
+                handle: handle.clone(),
+            });
+            if let Some(group) = group {
+                entity.insert(group);
+            }
+            entity.id()
+        });
+        app.update();
+        let current = |app: &App| {
+            entities.map(|entity| app.world().get::<GifPlayer>(entity).unwrap().current)
+        };
+        assert_eq!(current(&app), [3, 3]);
+
+        // reloaded with fewer frames
+        let mut assets = app.world_mut().resource_mut::<Assets<GifAsset>>();
+        let mut gif_asset = assets.get(&handle).unwrap().clone();
+        gif_asset.frames.truncate(2);
+        gif_asset.handles.truncate(2);
+        assets.insert(&handle, gif_asset).unwrap();
+        set_delta(&mut app, Duration::ZERO);
+        // the AssetEvent is only sent at the end of the update
+        app.update();
+        app.update();
+        assert_eq!(current(&app), [0, 0]);
+        let first = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .handles[0]
+            .clone();
+        for entity in entities {
+            assert_eq!(app.world().get::<Sprite>(entity).unwrap().image, first);
+        }
+
+        set_delta(&mut app, Duration::from_millis(1200));
+        app.update();
+        assert_eq!(current(&app), [0, 0]);
+    }
+
+    #[test]
+    fn test_atlas_max_size() {
+        assert_eq!(atlas_grid(100, 100, 4).unwrap(), (2, 2));
+        assert_eq!(atlas_grid(1024, 512, 64).unwrap(), (8, 8));
+        // 10 columns of 1000 pixels
+        assert!(atlas_grid(1000, 1000, 100).is_err());
+        assert!(atlas_grid(GifAtlas::MAX_SIZE + 1, 10, 1).is_err());
+    }
+
+    #[test]
+    fn test_invalid_clip() {
+        let mut app = build_app();
+
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.clips.insert("too_long".to_string(), 2..8);
+            },
+        );
+        for _ in 0..10 {
+            app.update();
+        }
+        let asset_server = app.world().resource::<AssetServer>();
+        assert!(asset_server.load_state(&handle).is_failed());
+    }
+
+    #[test]
+    fn test_animator() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.repeat = Some(GifRepeat::Infinite);
+                settings.clips.insert("sit".to_string(), 0..1);
+                settings.clips.insert("hop".to_string(), 1..4);
+                settings.clips.insert("all".to_string(), 0..4);
+            },
+        );
+        let once: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        wait_for_asset(&mut app, &handle);
+        wait_for_asset(&mut app, &once);
+        set_delta(&mut app, Duration::from_millis(200));
+
+        let animator = GifAnimator::new("sit", GifState::clip("sit"))
+            .with_state("hop", GifState::clip("hop"))
+            .with_state("all", GifState::clip("all"))
+            .with_transition(
+                GifTransition::new("sit", "hop").when(GifCondition::Bool("moving".into(), true)),
+            )
+            .with_transition(
+                GifTransition::new("hop", "sit")
+                    .when(GifCondition::Bool("moving".into(), false))
+                    .at_loop_end(),
+            )
+            .with_state("once", GifState::gif(once.clone()))
+            .with_transition(
+                GifTransition::from_any("all")
+                    .when(GifCondition::Trigger("all".into()))
+                    .matching_frame(),
+            )
+            .with_transition(
+                GifTransition::from_any("once").when(GifCondition::Trigger("once".into())),
+            )
+            .with_transition(
+                GifTransition::from_any("sit").when(GifCondition::Trigger("sit".into())),
+            );
+        let entity = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                animator,
+            ))
+            .id();
+        fn update(app: &mut App, entity: Entity) -> (String, usize) {
+            app.update();
+            let world = app.world();
+            (
+                world
+                    .get::<GifAnimator>(entity)
+                    .unwrap()
+                    .state()
+                    .to_string(),
+                world.get::<GifPlayer>(entity).unwrap().current,
+            )
+        }
+        fn params(app: &mut App, entity: Entity) -> Mut<'_, GifAnimator> {
+            app.world_mut().get_mut::<GifAnimator>(entity).unwrap()
+        }
+        assert_eq!(update(&mut app, entity), ("sit".to_string(), 0));
+
+        // immediate transition, the clip starts over and goes on for 200ms
+        params(&mut app, entity).set_bool("moving", true);
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 2));
+
+        // the transition waiting for the end of the loop is called off
+        params(&mut app, entity).set_bool("moving", false);
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 3));
+        params(&mut app, entity).set_bool("moving", true);
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 1));
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 2));
+
+        // wait for the end of the loop
+        params(&mut app, entity).set_bool("moving", false);
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 3));
+        assert_eq!(update(&mut app, entity).0, "sit");
+        assert_eq!(update(&mut app, entity), ("sit".to_string(), 0));
+
+        // the 2nd frame of "hop" goes on with the 2nd frame of "all", instead of the long 1st one
+        params(&mut app, entity).set_bool("moving", true);
+        assert_eq!(update(&mut app, entity), ("hop".to_string(), 2));
+        params(&mut app, entity).trigger("all");
+        assert_eq!(update(&mut app, entity), ("all".to_string(), 2));
+        assert_eq!(update(&mut app, entity), ("all".to_string(), 3));
+
+        // another GIF, then back to a clip of the GIF of the entity
+        params(&mut app, entity).set_bool("moving", false);
+        params(&mut app, entity).trigger("once");
+        assert_eq!(update(&mut app, entity), ("once".to_string(), 0));
+        assert_eq!(app.world().get::<Gif>(entity).unwrap().handle, once);
+        params(&mut app, entity).trigger("sit");
+        assert_eq!(update(&mut app, entity), ("sit".to_string(), 0));
+        assert_eq!(app.world().get::<Gif>(entity).unwrap().handle, handle);
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.clip(), Some("sit"));
+    }
+
+    #[test]
+    fn test_swap_handle() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+
+        // both last 1000, 200, 200 and 200ms
+        let five: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        let once: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        wait_for_asset(&mut app, &five);
+        wait_for_asset(&mut app, &once);
+        set_delta(&mut app, Duration::from_millis(1200));
+
+        let restarted = app
+            .world_mut()
+            .spawn(Gif {
+                handle: five.clone(),
+            })
+            .id();
+        let kept = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: five.clone(),
+                },
+                GifKeepProgress,
+            ))
+            .id();
+        app.update();
+        for entity in [restarted, kept] {
+            assert_eq!(app.world().get::<GifPlayer>(entity).unwrap().current, 2);
+            app.world_mut().get_mut::<Gif>(entity).unwrap().handle = once.clone();
+        }
+        set_delta(&mut app, Duration::ZERO);
+        app.update();
+
+        let world = app.world();
+        let frames = &world
+            .resource::<Assets<GifAsset>>()
+            .get(&once)
+            .unwrap()
+            .handles;
+        let player = world.get::<GifPlayer>(restarted).unwrap();
+        assert_eq!(player.current, 0);
+        assert_eq!(player.remaining, Some(0));
+        assert_eq!(world.get::<Sprite>(restarted).unwrap().image, frames[0]);
+
+        // 1200ms into the 1600ms of the first GIF, so 1200ms into the second one
+        let player = world.get::<GifPlayer>(kept).unwrap();
+        assert_eq!(player.current, 2);
+        assert_eq!(player.remaining, Some(0));
+        assert_eq!(world.get::<Sprite>(kept).unwrap().image, frames[2]);
+    }
+
+    #[test]
+    fn test_comment_markers() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = ::gif::Encoder::new(&mut bytes, 2, 2, &PALETTE).unwrap();
+            let comment = ::gif::AnyExtension(0xFE);
+            encoder
+                .write_raw_extension(comment, &[b"Made with a tool"])
+                .unwrap();
+            let frame = ::gif::Frame {
+                width: 2,
+                height: 2,
+                buffer: vec![1; 4].into(),
+                ..Default::default()
+            };
+            encoder.write_frame(&frame).unwrap();
+            // a comment split in several sub-blocks
+            encoder
+                .write_raw_extension(comment, &[b"foot", b"step"])
+                .unwrap();
+            encoder.write_frame(&frame).unwrap();
+            encoder.write_raw_extension(comment, &[b" spawn "]).unwrap();
+            encoder.write_frame(&frame).unwrap();
+        }
+        let markers = comment_markers(&bytes);
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[&0], vec!["Made with a tool"]);
+        assert_eq!(markers[&1], vec!["footstep"]);
+        assert_eq!(markers[&2], vec!["spawn"]);
+        assert_eq!(GifLoader::decode(&bytes).unwrap().0.len(), 3);
+    }
+
+    #[test]
+    fn test_frame_markers() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        record_events(&mut app);
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().resource::<AssetServer>().load_with_settings(
+            "frog_five.gif",
+            |settings: &mut GifLoaderSettings| {
+                settings.markers.insert(1, vec!["hop".to_string()]);
+                settings
+                    .markers
+                    .insert(3, vec!["land".to_string(), "dust".to_string()]);
+            },
+        );
+        wait_for_asset(&mut app, &handle);
+        app.world_mut()
+            .resource_mut::<Assets<GifAsset>>()
+            .get_mut_untracked(&handle)
+            .unwrap()
+            .add_marker(0, "sit");
+        set_delta(&mut app, Duration::from_millis(1600));
+
+        // a whole loop goes by in a single update
+        let entity = app.world_mut().spawn(Gif { handle }).id();
+        app.update();
+
+        let markers = &app.world().resource::<GifEvents>().markers;
+        assert_eq!(
+            markers,
+            &[
+                (entity, 0, "sit".to_string()),
+                (entity, 1, "hop".to_string()),
+                (entity, 3, "land".to_string()),
+                (entity, 3, "dust".to_string()),
+                (entity, 0, "sit".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_changed() {
+        use bevy::camera::visibility::SetViewVisibility;
+
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        record_events(&mut app);
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                GifEmitFrameChanged,
+            ))
+            .id();
+        // not opted in
+        app.world_mut().spawn(Gif { handle });
+        app.update();
+        app.update();
+        set_delta(&mut app, Duration::from_millis(100));
+        // still on the same frame
+        app.update();
+
+        let frames = &app.world().resource::<GifEvents>().frames;
+        assert_eq!(frames, &[(entity, 0, 1, 0), (entity, 1, 0, 1)]);
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert_eq!(player.loop_index(), 1);
+
+        // the frames played while hidden are not displayed
+        let mut gif = app.world_mut().entity_mut(entity);
+        gif.insert((GifCulling::SkipRender, InheritedVisibility::HIDDEN));
+        // the visibility systems are not running
+        gif.get_mut::<ViewVisibility>().unwrap().set_visible();
+        set_delta(&mut app, Duration::from_millis(700));
+        app.update();
+        assert_eq!(app.world().resource::<GifEvents>().frames.len(), 2);
+        // until it is visible again
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(InheritedVisibility::VISIBLE);
+        set_delta(&mut app, Duration::ZERO);
+        app.update();
+        // and so is the poster frame
+        app.world_mut()
+            .resource_mut::<GifAccessibility>()
+            .reduced_motion = true;
+        app.update();
+        let frames = &app.world().resource::<GifEvents>().frames;
+        assert_eq!(frames[2..], [(entity, 0, 2, 1), (entity, 2, 0, 1)]);
+    }
+
+    /// Custom clock for [test_clocks], running at half speed
+    #[derive(Default)]
+    struct Slow;
+
+    #[test]
+    fn test_clocks() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        app.register_gif_clock::<Slow>();
+        app.world_mut().resource_mut::<GifSettings>().default_clock = GifClock::Real;
+        app.init_resource::<Time<Slow>>();
+        app.add_systems(
+            First,
+            (|virtual_time: Res<Time<Virtual>>, mut slow: ResMut<Time<Slow>>| {
+                slow.advance_by(virtual_time.delta() / 2);
+            })
+            .after(bevy::time::TimeSystems),
+        );
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let mut spawn = |clock: Option<GifClock>| {
+            let mut entity = app.world_mut().spawn(Gif {
+                handle: handle.clone(),
+            });
+            if let Some(clock) = clock {
+                entity.insert(clock);
+            }
+            entity.id()
+        };
+        let virtual_gif = spawn(Some(GifClock::Virtual));
+        let real_gif = spawn(Some(GifClock::Real));
+        let fixed_gif = spawn(Some(GifClock::Fixed));
+        let slow_gif = spawn(Some(GifClock::custom::<Slow>()));
+        let default_gif = spawn(None);
+        let current = |app: &App| {
+            [virtual_gif, real_gif, fixed_gif, slow_gif, default_gif]
+                .map(|entity| app.world().get::<GifPlayer>(entity).unwrap().current)
+        };
+
+        app.update();
+        // 64 fixed timesteps of 15.625ms ran during the update
+        assert_eq!(current(&app), [1, 1, 1, 0, 1]);
+
+        // only the real time goes on
+        app.world_mut().resource_mut::<Time<Virtual>>().pause();
+        app.update();
+        assert_eq!(current(&app), [1, 0, 1, 0, 0]);
+
+        app.world_mut().resource_mut::<Time<Virtual>>().unpause();
+        app.update();
+        assert_eq!(current(&app), [0, 3, 0, 1, 3]);
+    }
+
+    #[test]
+    fn test_sync_group() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        record_events(&mut app);
+
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let first = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                GifSyncGroup(0),
+            ))
+            .id();
+        app.update();
+
+        // spawned later, but joining the group
+        let second = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                GifSyncGroup(0),
+            ))
+            .id();
+        let alone = app.world_mut().spawn(Gif { handle }).id();
+        set_delta(&mut app, Duration::from_millis(200));
+        app.update();
+        let current = |app: &App| {
+            [first, second, alone]
+                .map(|entity| app.world().get::<GifPlayer>(entity).unwrap().current)
+        };
+        assert_eq!(current(&app), [2, 2, 0]);
+
+        set_delta(&mut app, Duration::from_millis(400));
+        app.update();
+        assert_eq!(current(&app), [0, 0, 0]);
+        let loops = &app.world().resource::<GifEvents>().loops;
+        assert_eq!(loops.len(), 2);
+        assert!(loops.contains(&(first, Some(4))));
+        assert!(loops.contains(&(second, Some(4))));
+
+        // seeking a member seeks the whole group
+        set_delta(&mut app, Duration::ZERO);
+        app.world_mut()
+            .get_mut::<GifPlayer>(second)
+            .unwrap()
+            .seek_frame(3);
+        app.update();
+        assert_eq!(current(&app), [3, 3, 0]);
+
+        // and so does pausing a member
+        app.world_mut()
+            .get_mut::<GifPlayer>(second)
+            .unwrap()
+            .pause();
+        set_delta(&mut app, Duration::from_millis(200));
+        app.update();
+        assert_eq!(current(&app), [3, 3, 0]);
+        assert!(app.world().get::<GifPlayer>(first).unwrap().is_paused());
+
+        app.world_mut()
+            .get_mut::<GifPlayer>(first)
+            .unwrap()
+            .resume();
+        app.update();
+        assert_eq!(current(&app), [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_start_offset() {
+        /// Frames at which 8 gifs with a random offset start, with the same seed
+        fn random_starts(seed: u64) -> Vec<usize> {
+            let mut app = build_app();
+            app.register_gif_target::<Gif>();
+            app.insert_resource(GifRng::seeded(seed));
+            let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+            wait_for_asset(&mut app, &handle);
+            set_delta(&mut app, Duration::ZERO);
+            let entities: Vec<Entity> = (0..8)
+                .map(|_| {
+                    app.world_mut()
+                        .spawn((
+                            Gif {
+                                handle: handle.clone(),
+                            },
+                            GifStartOffset::Random,
+                        ))
+                        .id()
+                })
+                .collect();
+            app.update();
+            entities
+                .iter()
+                .map(|entity| app.world().get::<GifPlayer>(*entity).unwrap().current)
+                .collect()
+        }
+
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::ZERO);
+
+        let frame = app
+            .world_mut()
+            .spawn((
+                Gif {
+                    handle: handle.clone(),
+                },
+                GifStartOffset::Frame(2),
+            ))
+            .id();
+        let time = app
+            .world_mut()
+            .spawn((
+                Gif { handle },
+                GifStartOffset::Time(Duration::from_millis(1300)),
+            ))
+            .id();
+        // only the first GIF starts with the offset
+        let once: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        let infinite: Handle<GifAsset> = app.world().load_asset("frog_infinite.gif");
+        wait_for_asset(&mut app, &once);
+        wait_for_asset(&mut app, &infinite);
+        let switched = app
+            .world_mut()
+            .spawn((
+                Gif { handle: once },
+                GifStartOffset::Frame(2),
+                GifOnFinish::Switch(infinite.clone()),
+            ))
+            .id();
+        app.update();
+        let player = app.world().get::<GifPlayer>(frame).unwrap();
+        assert_eq!(player.current, 2);
+        assert_eq!(player.timer.elapsed(), Duration::ZERO);
+        let player = app.world().get::<GifPlayer>(time).unwrap();
+        assert_eq!(player.current, 2);
+        assert_eq!(player.timer.elapsed(), Duration::from_millis(100));
+        assert_eq!(app.world().get::<GifPlayer>(switched).unwrap().current, 2);
+
+        // the last two frames, then the switched-to GIF starts over
+        set_delta(&mut app, Duration::from_millis(400));
+        app.update();
+        set_delta(&mut app, Duration::ZERO);
+        app.update();
+        assert_eq!(app.world().get::<Gif>(switched).unwrap().handle, infinite);
+        assert_eq!(app.world().get::<GifPlayer>(switched).unwrap().current, 0);
+
+        let starts = random_starts(7);
+        assert_eq!(starts, random_starts(7));
+        assert!(starts.iter().any(|start| *start != starts[0]));
+    }
+
+    #[test]
+    fn test_culling() {
+        use bevy::camera::visibility::SetViewVisibility;
+
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entities =
+            [Some(GifCulling::Freeze), Some(GifCulling::SkipRender), None].map(|culling| {
+                let mut entity = app.world_mut().spawn((
+                    Gif {
+                        handle: handle.clone(),
+                    },
+                    InheritedVisibility::VISIBLE,
+                ));
+                if let Some(culling) = culling {
+                    entity.insert(culling);
+                }
+                // the visibility systems are not running
+                entity.get_mut::<ViewVisibility>().unwrap().set_visible();
+                entity.id()
+            });
+        let set_visibility = |app: &mut App, visibility: InheritedVisibility| {
+            for entity in entities {
+                app.world_mut().entity_mut(entity).insert(visibility);
+            }
+        };
+        // frame played, and frame shown by the sprite
+        let frames = |app: &App| {
+            let gif_asset = app
+                .world()
+                .resource::<Assets<GifAsset>>()
+                .get(&handle)
+                .unwrap();
+            entities.map(|entity| {
+                let shown = &app.world().get::<Sprite>(entity).unwrap().image;
+                (
+                    app.world().get::<GifPlayer>(entity).unwrap().current,
+                    gif_asset.handles.iter().position(|h| h == shown).unwrap(),
+                )
+            })
+        };
+
+        app.update();
+        assert_eq!(frames(&app), [(1, 1), (1, 1), (1, 1)]);
+
+        set_visibility(&mut app, InheritedVisibility::HIDDEN);
+        set_delta(&mut app, Duration::from_millis(200));
+        app.update();
+        assert_eq!(frames(&app), [(1, 1), (2, 1), (2, 2)]);
+
+        set_visibility(&mut app, InheritedVisibility::VISIBLE);
+        set_delta(&mut app, Duration::ZERO);
+        app.update();
+        assert_eq!(frames(&app), [(1, 1), (2, 2), (2, 2)]);
+
+        // without a GifCulling
+        app.world_mut()
+            .resource_mut::<GifSettings>()
+            .default_culling = GifCulling::Freeze;
+        set_visibility(&mut app, InheritedVisibility::HIDDEN);
+        set_delta(&mut app, Duration::from_millis(200));
+        app.update();
+        assert_eq!(frames(&app), [(1, 1), (3, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_view_culling() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        app.register_gif_target::<GifNode>();
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        // visible in the hierarchy, but the cameras never saw them
+        let sprites = [GifCulling::Freeze, GifCulling::SkipRender].map(|culling| {
+            app.world_mut()
+                .spawn((
+                    Gif {
+                        handle: handle.clone(),
+                    },
+                    culling,
+                    InheritedVisibility::VISIBLE,
+                ))
+                .id()
+        });
+        // the cameras don't update the ViewVisibility of UI nodes
+        let nodes = [GifCulling::Freeze, GifCulling::SkipRender].map(|culling| {
+            app.world_mut()
+                .spawn((
+                    GifNode {
+                        handle: handle.clone(),
+                    },
+                    culling,
+                    InheritedVisibility::VISIBLE,
+                ))
+                .id()
+        });
+        for entity in sprites.into_iter().chain(nodes) {
+            assert!(!app.world().get::<ViewVisibility>(entity).unwrap().get());
+        }
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap()
+            .clone();
+        let shown = |image: &Handle<Image>| gif_asset.handles.iter().position(|h| h == image);
+        let current =
+            |app: &App, entity: Entity| app.world().get::<GifPlayer>(entity).unwrap().current;
+
+        app.update();
+        // culled: frozen, or not shown
+        assert_eq!(current(&app, sprites[0]), 0);
+        assert_eq!(current(&app, sprites[1]), 1);
+        for entity in sprites {
+            let sprite = app.world().get::<Sprite>(entity).unwrap();
+            assert_eq!(shown(&sprite.image), Some(0));
+        }
+        // not culled
+        for entity in nodes {
+            assert_eq!(current(&app, entity), 1);
+            let node = app.world().get::<ImageNode>(entity).unwrap();
+            assert_eq!(shown(&node.image), Some(1));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "GifPlugin must be added before")]
+    fn test_plugin_after_target() {
+        use bevy::ecs::schedule::ScheduleLabel;
+
+        let mut app = build_app();
+        // the gif systems already run in Update
+        app.register_gif_target::<Gif>();
+        app.add_plugins(GifPlugin {
+            schedule: PostUpdate.intern(),
+            ..default()
+        });
+    }
+
+    /// Frames played by the gifs before and after the gif systems, for [test_plugin_settings]
+    #[derive(Resource, Default)]
+    struct SeenFrames {
+        before: Vec<usize>,
+        after: Vec<usize>,
+    }
+
+    #[test]
+    fn test_plugin_settings() {
+        use bevy::{ecs::schedule::ScheduleLabel, image::ImageSampler};
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(bevy::asset::AssetPlugin::default());
+        app.init_asset::<Image>();
+        app.init_asset::<StandardMaterial>();
+        app.init_asset::<ColorMaterial>();
+        app.init_asset::<TextureAtlasLayout>();
+        app.add_plugins(GifPlugin {
+            schedule: PostUpdate.intern(),
+            default_sampler: ImageSampler::nearest(),
+            settings: GifSettings {
+                speed: 2.,
+                despawn: false,
+                ..default()
+            },
+        });
+        app.init_resource::<SeenFrames>();
+        app.add_systems(
+            PostUpdate,
+            (
+                (|player: Single<&GifPlayer>, mut seen: ResMut<SeenFrames>| {
+                    seen.before.push(player.current);
+                })
+                .before(GifSystems::Prepare),
+                (|player: Single<&GifPlayer>, mut seen: ResMut<SeenFrames>| {
+                    seen.after.push(player.current);
+                })
+                .after(GifSystems::Animate),
+            ),
+        );
+
+        // frames last 1000, 200, 200 and 200ms, played once
+        let handle: Handle<GifAsset> = app.world().load_asset("frog_once.gif");
+        wait_for_asset(&mut app, &handle);
+        let gif_asset = app
+            .world()
+            .resource::<Assets<GifAsset>>()
+            .get(&handle)
+            .unwrap();
+        let image = app
+            .world()
+            .resource::<Assets<Image>>()
+            .get(&gif_asset.handles[0]);
+        assert!(matches!(
+            image.unwrap().sampler,
+            ImageSampler::Descriptor(_)
+        ));
+
+        set_delta(&mut app, Duration::from_millis(500));
+        let entity = app.world_mut().spawn((Gif { handle }, GifDespawn)).id();
+        app.update();
+        app.update();
+
+        let seen = app.world().resource::<SeenFrames>();
+        assert_eq!(seen.before, [0, 1]);
+        assert_eq!(seen.after, [1, 0]);
+        // finished, but not despawned
+        let player = app.world().get::<GifPlayer>(entity).unwrap();
+        assert!(player.timer.is_paused());
+    }
+
+    #[test]
+    fn test_accessibility() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        // frames last 1000, 200, 200 and 200ms
+        let handle: Handle<GifAsset> = app
+            .world()
+            .resource::<AssetServer>()
+            .load_with_settings("frog_five.gif", |settings: &mut GifLoaderSettings| {
+                settings.poster = 2
+            });
+        wait_for_asset(&mut app, &handle);
+        set_delta(&mut app, Duration::from_millis(1000));
+
+        let entities = [false, true].map(|essential| {
+            let mut entity = app.world_mut().spawn(Gif {
+                handle: handle.clone(),
+            });
+            if essential {
+                entity.insert(GifEssential);
+            }
+            entity.id()
+        });
+        // frame played, and frame shown by the sprite
+        let frames = |app: &App| {
+            let gif_asset = app
+                .world()
+                .resource::<Assets<GifAsset>>()
+                .get(&handle)
+                .unwrap();
+            entities.map(|entity| {
+                let shown = &app.world().get::<Sprite>(entity).unwrap().image;
+                (
+                    app.world().get::<GifPlayer>(entity).unwrap().current,
+                    gif_asset.handles.iter().position(|h| h == shown).unwrap(),
+                )
+            })
+        };
+        fn accessibility(app: &mut App) -> Mut<'_, GifAccessibility> {
+            app.world_mut().resource_mut::<GifAccessibility>()
+        }
+
+        accessibility(&mut app).speed = 0.5;
+        app.update();
+        assert_eq!(frames(&app), [(0, 0), (1, 1)]);
+
+        // at most a new frame every 500ms
+        accessibility(&mut app).speed = 1.;
+        accessibility(&mut app).max_fps = Some(2.);
+        set_delta(&mut app, Duration::from_millis(500));
+        app.update();
+        assert_eq!(frames(&app)[0], (1, 1));
+        set_delta(&mut app, Duration::from_millis(200));
+        app.update();
+        assert_eq!(frames(&app)[0], (2, 1));
+        app.update();
+        assert_eq!(frames(&app)[0], (3, 1));
+        app.update();
+        assert_eq!(frames(&app)[0], (0, 0));
+
+        // frozen on the poster frame, then back to the current one
+        accessibility(&mut app).max_fps = None;
+        accessibility(&mut app).reduced_motion = true;
+        let essential = frames(&app)[1];
+        set_delta(&mut app, Duration::from_millis(1000));
+        app.update();
+        assert_eq!(frames(&app)[0], (0, 2));
+        assert_ne!(frames(&app)[1], essential);
+        accessibility(&mut app).reduced_motion = false;
+        set_delta(&mut app, Duration::ZERO);
+        app.update();
+        assert_eq!(frames(&app)[0], (0, 0));
+
+        // the cap is in real time, even when the gifs are slowed down
+        app.world_mut().resource_mut::<GifSettings>().speed = 0.5;
+        accessibility(&mut app).max_fps = Some(2.);
+        set_delta(&mut app, Duration::from_millis(250));
+        for _ in 0..8 {
+            app.update();
+        }
+        assert_eq!(frames(&app)[0], (1, 1));
+        app.update();
+        app.update();
+        assert_eq!(frames(&app)[0], (2, 2));
+
+        // invalid caps are ignored
+        accessibility(&mut app).max_fps = Some(0.);
+        set_delta(&mut app, Duration::from_millis(400));
+        app.update();
+        assert_eq!(frames(&app)[0], (3, 3));
+    }
+
+    #[test]
+    fn test_long_hitch() {
+        let mut app = build_app();
+        app.register_gif_target::<Gif>();
+        record_events(&mut app);
+        // the virtual time is clamped
+        app.world_mut().resource_mut::<GifSettings>().default_clock = GifClock::Real;
+
+        // frames last 1000, 200, 200 and 200ms, looping forever or 5 times
+        let infinite: Handle<GifAsset> = app.world().load_asset("frog_infinite.gif");
+        let five: Handle<GifAsset> = app.world().load_asset("frog_five.gif");
+        wait_for_asset(&mut app, &infinite);
+        wait_for_asset(&mut app, &five);
+        set_delta(&mut app, Duration::ZERO);
+        let infinite = app.world_mut().spawn(Gif { handle: infinite }).id();
+        let five = app.world_mut().spawn(Gif { handle: five }).id();
+        app.update();
+
+        // 2250 loops of 1.6s, and half of the first frame
+        set_delta(&mut app, Duration::from_millis(3_600_500));
+        app.update();
+
+        let player = app.world().get::<GifPlayer>(infinite).unwrap();
+        assert_eq!(player.current, 0);
+        assert_eq!(player.timer.elapsed(), Duration::from_millis(500));
+        assert_eq!(player.loop_index(), 2250);
+        let player = app.world().get::<GifPlayer>(five).unwrap();
+        assert!(player.timer.is_paused());
+        assert_eq!(player.remaining, Some(0));
+
+        let events = app.world().resource::<GifEvents>();
+        assert_eq!(events.finished, [five]);
+        // only the last 256 loops (1024 frames) are stepped through, after a single event
+        // for the skipped ones
+        assert_eq!(events.loops.len(), 1 + 256 + 5);
+        assert_eq!(events.skipped, 2250 - 257);
+    }
 }
@@ -0,0 +1,73 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::gif::GifPlayer;
+
+/// Insert this component next to a gif to start it somewhere else than its first frame,
+/// so that identical gifs spawned together, such as a field of grass, do not look robotic.
+///
+/// ```ignore
+/// commands.spawn((Gif { handle: grass.clone() }, GifStartOffset::Random));
+/// ```
+///
+/// The offset is applied when the gif starts playing, within the clip (or GIF) being played.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum GifStartOffset {
+    /// Start from the frame `index`, or the closest one within the clip
+    Frame(usize),
+    /// Start from the frame shown this long after the beginning of a loop. Times longer than a loop wrap around.
+    Time(Duration),
+    /// Start anywhere within the loop, picked with the [GifRng]
+    Random,
+}
+
+impl GifStartOffset {
+    /// Request the seek starting `player` at this offset
+    pub(crate) fn seek(&self, player: &mut GifPlayer, rng: &mut GifRng) {
+        match *self {
+            GifStartOffset::Frame(index) => player.seek_frame(index),
+            GifStartOffset::Time(time) => player.seek_time(time),
+            GifStartOffset::Random => player.seek_progress(rng.next_f32()),
+        }
+    }
+}
+
+/// Random number generator picking the [GifStartOffset::Random] offsets.
+/// It is seeded randomly, insert it with a seed to get the same offsets on every run.
+///
+/// ```ignore
+/// app.insert_resource(GifRng::seeded(42));
+/// ```
+#[derive(Resource, Debug, Clone)]
+pub struct GifRng {
+    state: u64,
+}
+
+impl GifRng {
+    /// Generator whose sequence of offsets only depends on `seed`
+    pub fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next number of the sequence, between 0 (included) and 1 (excluded)
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // the 24 upper bits fit in the mantissa of a f32
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl Default for GifRng {
+    fn default() -> Self {
+        Self::seeded(RandomState::new().hash_one(0u8))
+    }
+}
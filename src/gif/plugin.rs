@@ -1,22 +1,204 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    image::ImageSampler,
+    prelude::*,
+};
 
 use crate::gif::{
-    GifAsset,
+    Gif, Gif2dMesh, Gif3d, GifAccessibility, GifAsset, GifClock, GifCulling, GifMaterial, GifNode,
+    clock::GifClockDeltas,
     components::GifLoader,
-    messages::GifDespawnMessage,
-    systems::{animate_gifs, despawn_gifs, initialize_gifs},
+    offset::GifRng,
+    sync::{GifSyncGroups, forward_sync_controls, next_sync_generation},
+    systems::{
+        accumulate_fixed_time, animate_gifs, drive_animators, end_animator_loops, finish_gifs,
+        initialize_gifs, read_custom_clock, take_fixed_time,
+    },
+    target::GifTarget,
 };
 
-pub struct GifPlugin;
+/// Loads and animates GIFs.
+///
+/// ```ignore
+/// app.add_plugins(GifPlugin::default());
+/// // or, to animate the gifs after the game logic
+/// app.add_plugins(GifPlugin {
+///     schedule: PostUpdate.intern(),
+///     default_sampler: ImageSampler::nearest(),
+///     ..default()
+/// });
+/// ```
+pub struct GifPlugin {
+    /// Schedule running the gif systems, [Update] by default. See [GifSystems] to order systems around them.
+    pub schedule: InternedScheduleLabel,
+    /// Sampler of the frames, for the GIFs without a [GifLoaderSettings::sampler](crate::GifLoaderSettings::sampler).
+    /// [ImageSampler::Default] by default, which is the one of the [ImagePlugin].
+    pub default_sampler: ImageSampler,
+    /// Initial value of the [GifSettings] resource
+    pub settings: GifSettings,
+}
+
+impl Default for GifPlugin {
+    fn default() -> Self {
+        Self {
+            schedule: Update.intern(),
+            default_sampler: ImageSampler::Default,
+            settings: GifSettings::default(),
+        }
+    }
+}
 
 impl Plugin for GifPlugin {
     fn build(&self, app: &mut App) {
+        if let Some(GifSchedule(schedule)) = app.world().get_resource::<GifSchedule>()
+            && *schedule != self.schedule
+        {
+            panic!(
+                "GifPlugin must be added before registering gif targets and clocks, which already run in {schedule:?}"
+            );
+        }
+        app.insert_resource(GifSchedule(self.schedule));
+        app.insert_resource(self.settings.clone());
         app.init_asset::<GifAsset>();
-        app.add_message::<GifDespawnMessage>();
-        app.init_asset_loader::<GifLoader>();
+        app.register_asset_loader(GifLoader {
+            default_sampler: self.default_sampler.clone(),
+        });
+        app.register_gif_target::<Gif>()
+            .register_gif_target::<GifNode>()
+            .register_gif_target::<Gif3d>()
+            .register_gif_target::<Gif2dMesh>()
+            .register_gif_target::<GifMaterial<StandardMaterial>>();
+    }
+}
+
+/// Settings of all the gifs, which can be changed at any time.
+///
+/// ```ignore
+/// fn slow_motion(mut settings: ResMut<GifSettings>) {
+///     settings.speed = 0.5;
+/// }
+/// ```
+#[derive(Resource, Debug, Clone)]
+pub struct GifSettings {
+    /// Speed of all the gifs, on top of their [GifPlayer::speed](crate::GifPlayer::speed). 1 by default.
+    /// Negative, infinite and NaN speeds freeze the gifs, as 0 does.
+    pub speed: f32,
+    /// Clock of the gifs without a [GifClock]. [GifClock::Virtual] by default.
+    pub default_clock: GifClock,
+    /// Culling of the gifs without a [GifCulling]. [GifCulling::AlwaysRun] by default.
+    pub default_culling: GifCulling,
+    /// Whether the finished gifs with a [GifDespawn](crate::GifDespawn) (or [GifOnFinish::Despawn](crate::GifOnFinish::Despawn))
+    /// are despawned. `true` by default.
+    pub despawn: bool,
+}
+
+impl Default for GifSettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            default_clock: GifClock::Virtual,
+            default_culling: GifCulling::AlwaysRun,
+            despawn: true,
+        }
+    }
+}
+
+/// Systems of the gifs, which run one after the other in the schedule of the [GifPlugin].
+///
+/// ```ignore
+/// app.add_systems(Update, move_characters.before(GifSystems::Prepare));
+/// app.add_systems(Update, read_frames.after(GifSystems::Animate));
+/// ```
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GifSystems {
+    /// Read the [GifClock]s, and follow the transitions of the [GifAnimator](crate::GifAnimator)s
+    Prepare,
+    /// Show the first frame of the gifs which were spawned, or whose GIF changed
+    Initialize,
+    /// Advance the gifs, and show their new frame
+    Animate,
+}
+
+/// Schedule running the gif systems, see [GifPlugin::schedule]
+#[derive(Resource)]
+struct GifSchedule(InternedScheduleLabel);
+
+/// Resources and systems shared by all the [GifTarget]s, added along the first one
+struct GifCorePlugin;
+
+impl Plugin for GifCorePlugin {
+    fn build(&self, app: &mut App) {
+        let schedule = gif_schedule(app);
+        app.init_resource::<GifSettings>();
+        app.init_resource::<GifAccessibility>();
+        app.init_resource::<GifClockDeltas>();
+        app.init_resource::<GifSyncGroups>();
+        app.init_resource::<GifRng>();
+        app.configure_sets(
+            schedule,
+            (
+                GifSystems::Prepare,
+                GifSystems::Initialize,
+                GifSystems::Animate,
+            )
+                .chain(),
+        );
+        app.add_systems(FixedUpdate, accumulate_fixed_time);
         app.add_systems(
-            Update,
-            (initialize_gifs, animate_gifs, despawn_gifs).chain(),
+            schedule,
+            (
+                take_fixed_time,
+                (next_sync_generation, forward_sync_controls).chain(),
+            )
+                .in_set(GifSystems::Prepare),
         );
     }
 }
+
+/// Schedule of the [GifPlugin], or [Update] for the targets registered without it.
+/// It can no longer change once the first target or clock is registered.
+fn gif_schedule(app: &mut App) -> InternedScheduleLabel {
+    app.world_mut()
+        .get_resource_or_insert_with(|| GifSchedule(Update.intern()))
+        .0
+}
+
+/// Extends [App] to animate custom [GifTarget]s.
+pub trait GifAppExt {
+    /// Initialize and animate the entities holding a `T`, the same way it is done for [Gif]s.
+    ///
+    /// The systems run in the schedule of the [GifPlugin], which must be added first,
+    /// or in [Update] without it.
+    fn register_gif_target<T: GifTarget>(&mut self) -> &mut Self;
+
+    /// Make [Time<T>] available to the gifs following [GifClock::custom::<T>()](crate::GifClock::custom).
+    /// Just like the targets, it must be registered after the [GifPlugin] is added.
+    fn register_gif_clock<T: Default + Send + Sync + 'static>(&mut self) -> &mut Self;
+}
+
+impl GifAppExt for App {
+    fn register_gif_target<T: GifTarget>(&mut self) -> &mut Self {
+        if !self.is_plugin_added::<GifCorePlugin>() {
+            self.add_plugins(GifCorePlugin);
+        }
+        let schedule = gif_schedule(self);
+        self.add_systems(
+            schedule,
+            (
+                drive_animators::<T>
+                    .in_set(GifSystems::Prepare)
+                    .before(forward_sync_controls),
+                initialize_gifs::<T>.in_set(GifSystems::Initialize),
+                animate_gifs::<T>.in_set(GifSystems::Animate),
+            ),
+        );
+        self.add_observer(finish_gifs::<T>);
+        self.add_observer(end_animator_loops::<T>);
+        self
+    }
+
+    fn register_gif_clock<T: Default + Send + Sync + 'static>(&mut self) -> &mut Self {
+        let schedule = gif_schedule(self);
+        self.add_systems(schedule, read_custom_clock::<T>.in_set(GifSystems::Prepare))
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::gif::{GifAsset, GifPlayer};
+
+/// Insert this component next to gifs to play them in lockstep, such as a row of torches,
+/// even if they were spawned at different times.
+///
+/// ```ignore
+/// for x in 0..10 {
+///     commands.spawn((Gif { handle: torch.clone() }, GifSyncGroup(0), Transform::from_xyz(x as f32 * 32., 0., 0.)));
+/// }
+/// ```
+///
+/// The members showing the same GIF share a single playback, which is computed once per update:
+/// they show the same frame, and trigger the same events. The playback starts with the
+/// [GifPlayer] of the first member. Controlling any member ([GifPlayer::seek_frame],
+/// [GifPlayer::play_clip], [GifPlayer::pause], [GifPlayer::set_speed]...) controls the whole
+/// group, so a [GifAnimator](crate::GifAnimator) on one member drives all of them.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GifSyncGroup(pub u32);
+
+/// Something that happened while a [GifPlayer] was playing, to replay on the members of its group
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum GifStep {
+    /// The frame started playing
+    Frame(usize),
+    /// A loop was completed, with this many loops left, after skipping this many loops
+    Looped(Option<u16>, u32),
+    Finished,
+}
+
+/// Playback shared by the members of a [GifSyncGroup]
+#[derive(Debug)]
+struct GifSyncPlayback {
+    /// Update during which the playback was last advanced
+    generation: u32,
+    player: GifPlayer,
+    /// Whether the frame must be shown again, during that update
+    changed: bool,
+    steps: Vec<GifStep>,
+}
+
+/// Playbacks of the [GifSyncGroup]s, for each GIF they show
+#[derive(Resource, Debug, Default)]
+pub(crate) struct GifSyncGroups {
+    generation: u32,
+    playbacks: HashMap<(GifSyncGroup, AssetId<GifAsset>), GifSyncPlayback>,
+}
+
+impl GifSyncGroups {
+    /// Play `player` as a member of `group`: the first member to play during an update
+    /// advances the group with `advance`, and the others copy it.
+    /// Return whether the frame must be shown again, and what happened.
+    pub(crate) fn play(
+        &mut self,
+        group: GifSyncGroup,
+        gif: AssetId<GifAsset>,
+        player: &mut GifPlayer,
+        advance: impl FnOnce(&mut GifPlayer) -> (bool, Vec<GifStep>),
+    ) -> (bool, Vec<GifStep>) {
+        let generation = self.generation;
+        let Some(playback) = self.playbacks.get_mut(&(group, gif)) else {
+            let (changed, steps) = advance(player);
+            // the controls of the first member are the ones of the group already
+            player.controlled = false;
+            self.playbacks.insert(
+                (group, gif),
+                GifSyncPlayback {
+                    generation,
+                    player: player.clone(),
+                    changed,
+                    steps: steps.clone(),
+                },
+            );
+            return (changed, steps);
+        };
+        player.forward_controls(&mut playback.player);
+        if playback.generation != generation {
+            playback.generation = generation;
+            (playback.changed, playback.steps) = advance(&mut playback.player);
+        }
+        let changed = playback.changed || player.current != playback.player.current;
+        // the frame shown by the target is up to each member
+        let (displayed, since_shown) = (player.displayed, player.since_shown);
+        *player = playback.player.clone();
+        (player.displayed, player.since_shown) = (displayed, since_shown);
+        (changed, playback.steps.clone())
+    }
+
+    /// Drop the playbacks of `gif`, so that its groups start over from their next member
+    pub(crate) fn forget(&mut self, gif: AssetId<GifAsset>) {
+        self.playbacks.retain(|(_, id), _| *id != gif);
+    }
+}
+
+/// Start a new update of the [GifSyncGroup]s, forgetting the ones without members
+pub(crate) fn next_sync_generation(mut groups: ResMut<GifSyncGroups>) {
+    let previous = groups.generation;
+    groups.generation = previous.wrapping_add(1);
+    groups
+        .playbacks
+        .retain(|_, playback| playback.generation == previous);
+}
+
+/// Hand the seeks and controls of the members over to the playback of their group,
+/// so that they apply before the first member advances it
+pub(crate) fn forward_sync_controls(
+    members: Query<(&GifSyncGroup, &mut GifPlayer)>,
+    mut groups: ResMut<GifSyncGroups>,
+) {
+    for (group, mut player) in members {
+        let Some(gif) = player.source else {
+            continue;
+        };
+        if let Some(playback) = groups.playbacks.get_mut(&(*group, gif)) {
+            player.forward_controls(&mut playback.player);
+        }
+    }
+}
@@ -1,180 +1,440 @@
-use bevy::{
-    asset::RenderAssetUsages,
-    prelude::*,
-    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
-};
+use std::{any::TypeId, time::Duration};
+
+use bevy::{ecs::system::StaticSystemParam, prelude::*};
 
-use crate::{
-    Gif3d, GifNode,
-    gif::{Gif, GifAsset, GifDespawn, GifPlayer, messages::GifDespawnMessage},
+use crate::gif::{
+    GifAccessibility, GifAsset, GifDespawn, GifEmitFrameChanged, GifEssential, GifKeepProgress,
+    GifOnFinish, GifPlayer, GifSettings,
+    animator::GifAnimator,
+    clock::{GifClock, GifClockDeltas, GifClocks, scale_delta},
+    culling::{GifCulling, is_hidden},
+    events::{GifFinished, GifFrameChanged, GifFrameEvent, GifLoopCompleted},
+    offset::{GifRng, GifStartOffset},
+    sync::{GifStep, GifSyncGroup, GifSyncGroups},
+    target::GifTarget,
 };
 
-/// Initialize the [Gif]'s [Sprite] / [GifNode]'s [ImageNode] / [Gif3d]'s [MeshMaterial3d] with the first image of the sequence.
-pub(crate) fn initialize_gifs(
-    mut gifs_q: Query<(
-        Option<(&Gif, &mut Sprite)>,
-        Option<(&GifNode, &mut ImageNode)>,
-        Option<(&Gif3d, &mut MeshMaterial3d<StandardMaterial>)>,
-        &mut GifPlayer,
-    )>,
-    mut gifs: ResMut<Assets<GifAsset>>,
-    asset_server: ResMut<AssetServer>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+/// A gif entity, alongside what its [GifTarget] needs to display its frames
+type GifQueryData<T> = (
+    Entity,
+    &'static T,
+    &'static mut GifPlayer,
+    Option<&'static GifOnFinish>,
+    Option<&'static GifClock>,
+    Option<&'static GifSyncGroup>,
+    Has<GifEmitFrameChanged>,
+    Has<GifEssential>,
+    GifVisibilityData,
+    <T as GifTarget>::Data,
+);
+
+/// What to do while a gif entity is hidden, and whether it is
+type GifVisibilityData = (
+    Option<&'static GifCulling>,
+    Option<&'static InheritedVisibility>,
+    Option<&'static ViewVisibility>,
+);
+
+/// A gif entity to initialize, whether to keep its progress when its GIF changes, and where to start it
+type GifInitQueryData<T> = (
+    Entity,
+    Ref<'static, T>,
+    &'static mut GifPlayer,
+    Has<GifKeepProgress>,
+    Option<&'static GifStartOffset>,
+    Has<GifEmitFrameChanged>,
+    <T as GifTarget>::Data,
+);
+
+/// A gif entity, and what to do once it is finished
+type GifFinishQueryData<T> = (
+    &'static mut T,
+    &'static mut GifPlayer,
+    Option<&'static GifOnFinish>,
+    Has<GifDespawn>,
+);
+
+/// Initialize the [GifTarget]s (such as a [Gif](crate::Gif)'s [Sprite]) with the first image of the sequence.
+/// This happens again when the GIF of a target changes, or is modified (such as by a hot reload).
+/// A [GifStartOffset] is applied the first time.
+pub(crate) fn initialize_gifs<T: GifTarget>(
+    gifs_q: Query<GifInitQueryData<T>>,
+    (gifs, mut asset_events): (Res<Assets<GifAsset>>, MessageReader<AssetEvent<GifAsset>>),
+    mut groups: ResMut<GifSyncGroups>,
+    mut rng: ResMut<GifRng>,
+    mut commands: Commands,
+    mut param: StaticSystemParam<T::Param>,
 ) {
-    for (gif_option, gifnode_option, gif3d_option, mut player) in gifs_q.iter_mut() {
-        let handle = if let Some((gif, _)) = gif_option {
-            gif.handle.clone()
-        } else if let Some((gif_node, _)) = gifnode_option {
-            gif_node.handle.clone()
-        } else if let Some((gif3d, _)) = gif3d_option {
-            gif3d.handle.clone()
-        } else {
-            panic!("Unexpected error: a GifPlayer was inserted in an unknown entity");
-        };
-
-        if let Some(GifAsset {
-            frames,
-            handles,
-            times,
-        }) = gifs.get_mut(&handle)
+    // The frames of a modified GIF may no longer match the players, which start over
+    let modified: Vec<AssetId<GifAsset>> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for &id in &modified {
+        groups.forget(id);
+    }
+    for (entity, target, mut player, keep_progress, start_offset, emit_frame_changed, mut data) in
+        gifs_q
+    {
+        let handle = target.handle();
+        if player
+            .source
+            .is_some_and(|source| modified.contains(&source))
         {
-            if handles.len() != 0 {
-                // Already loaded, continue
-                continue;
+            player.source = None;
+        }
+        if player.source == Some(handle.id()) {
+            // Already initialized, continue
+            continue;
+        }
+        // The handle was swapped: the previous GIF is still around at this point, even if
+        // the new one is not loaded yet
+        if keep_progress
+            && target.is_changed()
+            && let Some(previous) = player.source.and_then(|id| gifs.get(id))
+            && player.fits(previous)
+        {
+            let progress = player.progress(previous);
+            player.seek_progress(progress);
+        }
+        if let Some(gif_asset) = gifs.get(handle) {
+            // initialize timer and get first frame
+            // indexing is fine here, because this is called after `asset_server.load()`,
+            // which would panic if there is an issue with the GIF file.
+            player.start(gif_asset);
+            player.source = Some(handle.id());
+            if !player.offset_applied
+                && let Some(start_offset) = start_offset
+            {
+                player.offset_applied = true;
+                start_offset.seek(&mut player, &mut rng);
+                player.apply_seek(gif_asset);
             }
-            // Build all frames and store them
-            for frame in frames.iter() {
-                let image = Image::new_fill(
-                    Extent3d {
-                        width: frame.width,
-                        height: frame.height,
-                        depth_or_array_layers: 1,
-                    },
-                    TextureDimension::D2,
-                    &frame.rgba,
-                    TextureFormat::Rgba8UnormSrgb,
-                    RenderAssetUsages::all(),
-                );
-                let handle = asset_server.add(image);
-                handles.push(handle);
+            // load it to the target
+            target.initialize(&mut data, &mut param, gif_asset);
+            target.show_frame(&mut data, &mut param, gif_asset, player.current);
+            let current = player.current;
+            display_frame(
+                &mut commands,
+                entity,
+                &mut player,
+                current,
+                emit_frame_changed,
+            );
+            trigger_markers(&mut commands, entity, gif_asset, player.current);
+        }
+    }
+}
+
+/// Update the [GifPlayer] of all [GifTarget] entities.
+/// If the timer expires, or if a seek was requested, we update the player and show the new frame on the target, accordingly to the known config.
+/// The members of a [GifSyncGroup] share the same playback, hidden gifs follow their [GifCulling],
+/// and the others the [GifAccessibility] settings unless they are [GifEssential].
+pub(crate) fn animate_gifs<T: GifTarget>(
+    gifs_q: Query<GifQueryData<T>>,
+    gifs: Res<Assets<GifAsset>>,
+    clocks: GifClocks,
+    mut groups: ResMut<GifSyncGroups>,
+    (settings, accessibility): (Res<GifSettings>, Res<GifAccessibility>),
+    mut commands: Commands,
+    mut param: StaticSystemParam<T::Param>,
+) {
+    // Shortest time a frame is shown for, invalid caps being ignored like invalid speeds
+    let min_interval = match accessibility.max_fps {
+        Some(max_fps) if max_fps > 0. => {
+            Some(Duration::try_from_secs_f32(1. / max_fps).unwrap_or(Duration::MAX))
+        }
+        Some(max_fps) => {
+            if accessibility.is_changed() {
+                warn!("Ignoring GifAccessibility max_fps {max_fps}");
             }
-            // Get first frame and load it to the sprite
-            let frame = frames.first().unwrap();
-            let handle = handles.first().unwrap();
-            // unwrap()-ing is fine here, because this is called after `asset_server.load()`,
-            // which would panic if there is an issue with the GIF file.
-            if let Some((_, mut sprite)) = gif_option {
-                // just replacing the image allow to not overwrite previously given members (see [brothers example](examples/brothers.rs#spawn_flipped_larger_gif).)
-                // same principle for other kinds of gif
-                sprite.image = handle.clone();
+            None
+        }
+        None => None,
+    };
+    for (
+        entity,
+        target,
+        mut player,
+        on_finish,
+        clock,
+        sync_group,
+        emit_frame_changed,
+        essential,
+        (culling, inherited, view),
+        mut data,
+    ) in gifs_q
+    {
+        let handle = target.handle();
+        if player.source != Some(handle.id()) {
+            // Not initialized yet
+            continue;
+        }
+        let culling = culling.copied().unwrap_or(settings.default_culling);
+        let hidden = culling != GifCulling::AlwaysRun && is_hidden::<T>(inherited, view);
+        if hidden && culling == GifCulling::Freeze {
+            continue;
+        }
+        if let Some(gif_asset) = gifs.get(handle) {
+            if !player.fits(gif_asset) {
+                // Modified: it starts over once its AssetEvent is read
+                continue;
             }
-            if let Some((_, mut image_node)) = gifnode_option {
-                image_node.image = handle.clone();
+            if !essential && accessibility.reduced_motion {
+                if player.displayed != Some(gif_asset.poster) {
+                    target.show_frame(&mut data, &mut param, gif_asset, gif_asset.poster);
+                    let poster = gif_asset.poster;
+                    display_frame(
+                        &mut commands,
+                        entity,
+                        &mut player,
+                        poster,
+                        emit_frame_changed,
+                    );
+                }
+                continue;
+            }
+            let mut delta = clocks.delta(clock);
+            player.since_shown = player.since_shown.saturating_add(clocks.real_delta());
+            // Too soon to show a new frame
+            let capped =
+                !essential && min_interval.is_some_and(|interval| player.since_shown < interval);
+            if !essential {
+                delta = scale_delta(delta, accessibility.speed);
             }
-            if let Some((_, mm)) = gif3d_option {
-                if let Some(mat) = materials.get_mut(&mm.0) {
-                    mat.base_color_texture = Some(handle.clone());
-                    mat.alpha_mode = AlphaMode::Blend;
+            let advance =
+                |player: &mut GifPlayer| advance_player(player, gif_asset, delta, on_finish);
+            let (changed, steps) = match sync_group {
+                Some(group) => groups.play(*group, handle.id(), &mut player, advance),
+                None => advance(&mut player),
+            };
+            for step in steps {
+                match step {
+                    GifStep::Frame(index) => {
+                        trigger_markers(&mut commands, entity, gif_asset, index);
+                    }
+                    GifStep::Looped(loops_left, skipped) => {
+                        commands.trigger(GifLoopCompleted {
+                            entity,
+                            loops_left,
+                            skipped,
+                        });
+                    }
+                    GifStep::Finished => commands.trigger(GifFinished { entity }),
                 }
             }
-
-            // initialize timer
-            player.current = 0; // first frame
-            player.timer = Timer::new(frame.duration, TimerMode::Repeating);
-            player.remaining = *times;
+            // Otherwise, the frame will be shown once the gif is visible again, or once it is no longer too soon
+            if (changed || player.displayed != Some(player.current)) && !hidden && !capped {
+                target.show_frame(&mut data, &mut param, gif_asset, player.current);
+                let current = player.current;
+                display_frame(
+                    &mut commands,
+                    entity,
+                    &mut player,
+                    current,
+                    emit_frame_changed,
+                );
+                player.since_shown = Duration::ZERO;
+            }
         }
     }
 }
 
-/// Update the [GifPlayer] of all [Gif]s / [GifNode]s / [Gif3d] entities.
-/// If the timer expires, we update the player and the [Sprite] / [ImageNode] image, accordingly to the known config.
-/// It updates the [MeshMaterial3d] for 3d objects.
-pub(crate) fn animate_gifs(
-    gifs_q: Query<(
-        Option<(&Gif, &mut Sprite)>,
-        Option<(&GifNode, &mut ImageNode)>,
-        Option<(&Gif3d, &mut MeshMaterial3d<StandardMaterial>)>,
-        &mut GifPlayer,
-    )>,
-    gifs: Res<Assets<GifAsset>>,
-    time: Res<Time>,
-    mut writer: MessageWriter<GifDespawnMessage>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+/// Record that the target of `entity` displays the frame `index`, after [GifTarget::show_frame].
+/// Trigger a [GifFrameChanged] if it replaces another frame, and the entity asked for it.
+fn display_frame(
+    commands: &mut Commands,
+    entity: Entity,
+    player: &mut GifPlayer,
+    index: usize,
+    emit_frame_changed: bool,
 ) {
-    for (gif_option, gifnode_option, gif3d_option, mut player) in gifs_q {
-        let handle = if let Some((gif, _)) = gif_option {
-            gif.handle.clone()
-        } else if let Some((gif_node, _)) = gifnode_option {
-            gif_node.handle.clone()
-        } else if let Some((gif3d, _)) = gif3d_option {
-            gif3d.handle.clone()
-        } else {
-            panic!("Unexpected error: a GifPlayer was inserted in an unknown entity");
-        };
-
-        if let Some(gif_asset) = gifs.get(&handle) {
-            player.timer.tick(time.delta());
-            if player.timer.is_finished() {
-                // Update timer
-                player.current = (player.current + 1) % gif_asset.frames.len();
-                let frame = &gif_asset.frames[player.current];
-                let new_duration = frame.duration;
-
-                if player.current == 0 {
-                    // That means we just ended a loop !
-                    if let Some(remaining) = player.remaining {
-                        if remaining == 0 {
-                            player.timer.pause();
-                            writer.write(GifDespawnMessage(handle.clone()));
-                        } else {
-                            player.remaining = Some(remaining - 1);
-                        }
-                    }
-                    // no else because it means it is an infinite-looping GIF.
-                }
-                player.timer.set_duration(new_duration);
-                player.timer.reset();
+    let previous = player.displayed.replace(index);
+    if emit_frame_changed
+        && let Some(previous) = previous
+        && previous != index
+    {
+        commands.trigger(GifFrameChanged {
+            entity,
+            previous,
+            current: index,
+            loop_index: player.loop_index(),
+        });
+    }
+}
 
-                // Update sprite
-                let handle = gif_asset.handles[player.current].clone();
-                if let Some((_, mut sprite)) = gif_option {
-                    sprite.image = handle.clone();
-                }
-                if let Some((_, mut image_node)) = gifnode_option {
-                    image_node.image = handle.clone();
-                }
-                if let Some((_, mm)) = gif3d_option {
-                    if let Some(mat) = materials.get_mut(&mm.0) {
-                        mat.base_color_texture = Some(handle.clone());
-                        mat.alpha_mode = AlphaMode::Blend;
+/// How many frames, at most, a gif steps through in a single update, before skipping whole loops
+const MAX_STEPS: usize = 1024;
+
+/// Apply the requested seek, then play `delta` of the GIF.
+/// Return whether the frame must be shown again, and what happened, in order.
+fn advance_player(
+    player: &mut GifPlayer,
+    gif_asset: &GifAsset,
+    delta: Duration,
+    on_finish: Option<&GifOnFinish>,
+) -> (bool, Vec<GifStep>) {
+    let mut changed = player.apply_seek(gif_asset);
+    let mut steps = vec![];
+    // A paused timer means all loops are over
+    if player.is_paused() || player.timer.is_paused() {
+        return (changed, steps);
+    }
+    // Time left over by a frame is carried to the next ones,
+    // which means several frames (and loops) can go by in a single update
+    let mut elapsed = player
+        .timer
+        .elapsed()
+        .saturating_add(scale_delta(delta, player.speed()));
+    // After a long hitch, skip the whole loops rather than stepping through each of their frames:
+    // only the last ones are played, triggering their events, while the skipped ones are
+    // reported by a single GifLoopCompleted
+    let loop_nanos = player.loop_duration(gif_asset).as_nanos();
+    let stepped_loops = (MAX_STEPS / player.loop_len().max(1)).max(1) as u128;
+    if loop_nanos > 0 && elapsed.as_nanos() / loop_nanos > stepped_loops {
+        let mut loops = elapsed.as_nanos() / loop_nanos - stepped_loops;
+        if let Some(remaining) = player.remaining {
+            // the last loops, including the one finishing the gif, are still stepped through
+            loops = loops.min((u128::from(remaining) + 1).saturating_sub(stepped_loops));
+        }
+        let left = elapsed.as_nanos() - loops * loop_nanos;
+        elapsed = Duration::from_nanos(u64::try_from(left).unwrap_or(u64::MAX));
+        if loops > 0 {
+            player.skip_loops(loops);
+            let skipped = u32::try_from(loops - 1).unwrap_or(u32::MAX);
+            steps.push(GifStep::Looped(player.remaining, skipped));
+        }
+    }
+    while elapsed >= player.timer.duration() {
+        elapsed -= player.timer.duration();
+        changed = true;
+
+        // Update timer
+        let previous = player.current;
+        let looped = player.step();
+        let new_duration = gif_asset.frames[player.current].duration;
+        player.timer.set_duration(new_duration);
+
+        if looped {
+            // That means we just ended a loop !
+            steps.push(GifStep::Looped(player.remaining, 0));
+            if let Some(remaining) = player.remaining {
+                if remaining == 0 {
+                    // A removed gif keeps showing its last frame too
+                    if matches!(
+                        on_finish,
+                        Some(GifOnFinish::HoldLastFrame | GifOnFinish::Remove)
+                    ) {
+                        player.current = previous;
+                        player
+                            .timer
+                            .set_duration(gif_asset.frames[previous].duration);
                     }
+                    player.timer.pause();
+                    steps.push(GifStep::Finished);
+                    elapsed = Duration::ZERO;
+                    break;
+                } else {
+                    player.remaining = Some(remaining - 1);
                 }
             }
+            // no else because it means it is an infinite-looping GIF.
         }
+        steps.push(GifStep::Frame(player.current));
     }
+    player.timer.set_elapsed(elapsed);
+    (changed, steps)
+}
+
+/// Accumulate the fixed timesteps, for the gifs following [GifClock::Fixed]
+pub(crate) fn accumulate_fixed_time(time: Res<Time<Fixed>>, mut deltas: ResMut<GifClockDeltas>) {
+    deltas.fixed_accumulated += time.delta();
 }
 
-/// Triggered when a GIF with a finite number of loops reaches its end.
-/// Despawn the relevant entity.
-pub(crate) fn despawn_gifs(
+/// Hand the fixed timesteps which ran since the last update over to the gifs
+pub(crate) fn take_fixed_time(mut deltas: ResMut<GifClockDeltas>) {
+    let deltas = &mut *deltas;
+    deltas.fixed = std::mem::take(&mut deltas.fixed_accumulated);
+}
+
+/// Copy the delta of the custom clock [Time<T>], for the gifs following [GifClock::custom]
+pub(crate) fn read_custom_clock<T: Default + Send + Sync + 'static>(
+    time: Res<Time<T>>,
+    mut deltas: ResMut<GifClockDeltas>,
+) {
+    deltas.custom.insert(TypeId::of::<T>(), time.delta());
+}
+
+/// Trigger a [GifFrameEvent] for each marker of the frame `index`, which just started playing
+fn trigger_markers(commands: &mut Commands, entity: Entity, gif_asset: &GifAsset, index: usize) {
+    for marker in gif_asset.markers(index) {
+        commands.trigger(GifFrameEvent {
+            entity,
+            frame: index,
+            marker: marker.clone(),
+        });
+    }
+}
+
+/// Observe [GifFinished] and apply the [GifOnFinish] of the entity, if any.
+/// [GifDespawn] is the same as [GifOnFinish::Despawn], both are ignored unless [GifSettings::despawn] is set.
+pub(crate) fn finish_gifs<T: GifTarget>(
+    finished: On<GifFinished>,
     mut commands: Commands,
-    mut reader: MessageReader<GifDespawnMessage>,
-    gif_q: Query<(Option<&Gif>, Option<&GifNode>, Option<&Gif3d>, Entity), With<GifDespawn>>,
+    mut gif_q: Query<GifFinishQueryData<T>>,
+    settings: Res<GifSettings>,
 ) {
-    for GifDespawnMessage(handle) in reader.read() {
-        for (gif_option, gifnode_option, gif3d_option, entity) in gif_q {
-            let gif_handle = if let Some(gif) = gif_option {
-                gif.handle.clone()
-            } else if let Some(gif_node) = gifnode_option {
-                gif_node.handle.clone()
-            } else if let Some(gif3d) = gif3d_option {
-                gif3d.handle.clone()
-            } else {
-                panic!("Unexpected error: a GifPlayer was inserted in an unknown entity");
-            };
-            if gif_handle.id() == handle.id() {
-                commands.entity(entity).despawn();
-            }
+    let entity = finished.entity;
+    let Ok((mut target, mut player, on_finish, despawn)) = gif_q.get_mut(entity) else {
+        return;
+    };
+    if despawn && settings.despawn {
+        commands.entity(entity).despawn();
+        return;
+    }
+    match on_finish {
+        Some(GifOnFinish::Despawn) if settings.despawn => {
+            commands.entity(entity).despawn();
+        }
+        Some(GifOnFinish::Remove) => {
+            commands
+                .entity(entity)
+                .remove::<(T, GifPlayer, GifOnFinish)>();
+        }
+        Some(GifOnFinish::Hide) => {
+            commands.entity(entity).insert(Visibility::Hidden);
         }
+        Some(GifOnFinish::Switch(handle)) => {
+            target.set_handle(handle.clone());
+            // Initialize the player again, even if it is the same GIF
+            player.source = None;
+        }
+        // Handled while animating, or despawn handling is disabled
+        Some(
+            GifOnFinish::HoldLastFrame | GifOnFinish::ResetToFirstFrame | GifOnFinish::Despawn,
+        )
+        | None => {}
+    }
+}
+
+/// Follow the transitions of the [GifAnimator]s, before their GIFs are initialized and animated.
+pub(crate) fn drive_animators<T: GifTarget>(
+    animators_q: Query<(&mut GifAnimator, &mut T, &mut GifPlayer)>,
+) {
+    for (mut animator, mut target, mut player) in animators_q {
+        animator.update(&mut target, &mut player);
+    }
+}
+
+/// Observe [GifLoopCompleted] to follow the [GifAnimator] transitions waiting for the end of a loop.
+pub(crate) fn end_animator_loops<T: GifTarget>(
+    looped: On<GifLoopCompleted>,
+    mut animators_q: Query<(&mut GifAnimator, &mut T, &mut GifPlayer)>,
+) {
+    if let Ok((mut animator, mut target, mut player)) = animators_q.get_mut(looped.entity) {
+        animator.end_loop(&mut target, &mut player);
     }
 }
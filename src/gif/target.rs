@@ -0,0 +1,323 @@
+use bevy::{
+    ecs::{
+        component::Mutable,
+        query::{QueryData, QueryItem},
+        system::{SystemParam, SystemParamItem},
+    },
+    math::Affine2,
+    prelude::*,
+};
+
+use crate::gif::{Gif, Gif2dMesh, Gif3d, GifAsset, GifMaterial, GifNode, GifSharedMaterial};
+
+/// A component which displays the frames of a [GifAsset] on something.
+///
+/// [Gif], [GifNode], [Gif3d], [Gif2dMesh] and [GifMaterial] are the built-in targets. Implement this trait
+/// to animate anything else, then register the target with [GifAppExt::register_gif_target].
+/// The target component should require a [GifPlayer](crate::GifPlayer), like the built-in ones do.
+///
+/// ```ignore
+/// #[derive(Component)]
+/// #[require(MeshMaterial2d<ColorMaterial>, GifPlayer)]
+/// struct GifColorMaterial {
+///     handle: Handle<GifAsset>,
+/// }
+///
+/// impl GifTarget for GifColorMaterial {
+///     type Data = &'static MeshMaterial2d<ColorMaterial>;
+///     type Param = ResMut<'static, Assets<ColorMaterial>>;
+///
+///     fn handle(&self) -> &Handle<GifAsset> {
+///         &self.handle
+///     }
+///
+///     fn set_handle(&mut self, handle: Handle<GifAsset>) {
+///         self.handle = handle;
+///     }
+///
+///     fn show_frame(
+///         &self,
+///         material: &mut &MeshMaterial2d<ColorMaterial>,
+///         materials: &mut ResMut<Assets<ColorMaterial>>,
+///         gif_asset: &GifAsset,
+///         index: usize,
+///     ) {
+///         if let Some(mat) = materials.get_mut(&material.0) {
+///             mat.texture = Some(gif_asset.handles[index].clone());
+///         }
+///     }
+/// }
+///
+/// app.register_gif_target::<GifColorMaterial>();
+/// ```
+///
+/// An entity should hold a single target, otherwise each of them would advance its [GifPlayer](crate::GifPlayer).
+pub trait GifTarget: Component<Mutability = Mutable> {
+    /// Components of the entity needed to show a frame. It must not access the target itself.
+    type Data: QueryData + 'static;
+    /// Anything else needed to show a frame, such as the assets of a material.
+    type Param: SystemParam + 'static;
+
+    /// Whether the cameras update the [ViewVisibility] of the entity, as they do for meshes and sprites.
+    /// Otherwise, only its [InheritedVisibility] tells whether it is hidden, as for UI nodes.
+    /// See [GifCulling](crate::GifCulling).
+    const VIEW_CULLED: bool = true;
+
+    /// The GIF to play
+    fn handle(&self) -> &Handle<GifAsset>;
+
+    /// Play another GIF, used by [GifOnFinish::Switch](crate::GifOnFinish::Switch)
+    fn set_handle(&mut self, handle: Handle<GifAsset>);
+
+    /// Prepare the target before its first frame is shown, such as setting up a material.
+    /// Does nothing by default.
+    fn initialize(
+        &self,
+        _data: &mut QueryItem<'_, '_, Self::Data>,
+        _param: &mut SystemParamItem<'_, '_, Self::Param>,
+        _gif_asset: &GifAsset,
+    ) {
+    }
+
+    /// Show the frame at `index` of `gif_asset`.
+    fn show_frame(
+        &self,
+        data: &mut QueryItem<'_, '_, Self::Data>,
+        param: &mut SystemParamItem<'_, '_, Self::Param>,
+        gif_asset: &GifAsset,
+        index: usize,
+    );
+}
+
+impl GifTarget for Gif {
+    type Data = &'static mut Sprite;
+    type Param = ();
+
+    fn handle(&self) -> &Handle<GifAsset> {
+        &self.handle
+    }
+
+    fn set_handle(&mut self, handle: Handle<GifAsset>) {
+        self.handle = handle;
+    }
+
+    /// In atlas mode, only the atlas index changes once the atlas is set.
+    fn show_frame(
+        &self,
+        sprite: &mut Mut<'_, Sprite>,
+        _: &mut (),
+        gif_asset: &GifAsset,
+        index: usize,
+    ) {
+        // just replacing the image allow to not overwrite previously given members (see [brothers example](examples/brothers.rs#spawn_flipped_larger_gif).)
+        // same principle for other kinds of gif
+        match (&gif_asset.atlas, sprite.texture_atlas.as_mut()) {
+            (Some(atlas), Some(texture_atlas)) if texture_atlas.layout == atlas.layout => {
+                texture_atlas.index = index;
+            }
+            (Some(atlas), _) => {
+                sprite.image = atlas.image.clone();
+                sprite.texture_atlas = Some(atlas.texture_atlas(index));
+            }
+            (None, _) => sprite.image = gif_asset.handles[index].clone(),
+        }
+    }
+}
+
+impl GifTarget for GifNode {
+    type Data = &'static mut ImageNode;
+    type Param = ();
+
+    const VIEW_CULLED: bool = false;
+
+    fn handle(&self) -> &Handle<GifAsset> {
+        &self.handle
+    }
+
+    fn set_handle(&mut self, handle: Handle<GifAsset>) {
+        self.handle = handle;
+    }
+
+    /// In atlas mode, only the atlas index changes once the atlas is set.
+    fn show_frame(
+        &self,
+        image_node: &mut Mut<'_, ImageNode>,
+        _: &mut (),
+        gif_asset: &GifAsset,
+        index: usize,
+    ) {
+        match (&gif_asset.atlas, image_node.texture_atlas.as_mut()) {
+            (Some(atlas), Some(texture_atlas)) if texture_atlas.layout == atlas.layout => {
+                texture_atlas.index = index;
+            }
+            (Some(atlas), _) => {
+                image_node.image = atlas.image.clone();
+                image_node.texture_atlas = Some(atlas.texture_atlas(index));
+            }
+            (None, _) => image_node.image = gif_asset.handles[index].clone(),
+        }
+    }
+}
+
+impl GifTarget for Gif3d {
+    type Data = (
+        &'static mut MeshMaterial3d<StandardMaterial>,
+        Has<GifSharedMaterial>,
+    );
+    type Param = ResMut<'static, Assets<StandardMaterial>>;
+
+    fn handle(&self) -> &Handle<GifAsset> {
+        &self.handle
+    }
+
+    fn set_handle(&mut self, handle: Handle<GifAsset>) {
+        self.handle = handle;
+    }
+
+    /// GIFs are often transparent, so the material is blended. It can be changed afterwards.
+    fn initialize(
+        &self,
+        (material, shared): &mut (Mut<'_, MeshMaterial3d<StandardMaterial>>, bool),
+        materials: &mut ResMut<Assets<StandardMaterial>>,
+        _: &GifAsset,
+    ) {
+        if !*shared {
+            own_material(&mut material.0, materials);
+        }
+        if let Some(mat) = materials.get_mut(&material.0) {
+            mat.alpha_mode = AlphaMode::Blend;
+        }
+    }
+
+    /// In atlas mode, the uv transform selects the frame within the atlas.
+    fn show_frame(
+        &self,
+        (material, _): &mut (Mut<'_, MeshMaterial3d<StandardMaterial>>, bool),
+        materials: &mut ResMut<Assets<StandardMaterial>>,
+        gif_asset: &GifAsset,
+        index: usize,
+    ) {
+        if let Some(mat) = materials.get_mut(&material.0) {
+            show_material_frame(
+                mat,
+                &[|mat, image| mat.base_color_texture = Some(image)],
+                Some(|mat, uv_transform| mat.uv_transform = uv_transform),
+                gif_asset,
+                index,
+            );
+        }
+    }
+}
+
+impl GifTarget for Gif2dMesh {
+    type Data = (
+        &'static mut MeshMaterial2d<ColorMaterial>,
+        Has<GifSharedMaterial>,
+    );
+    type Param = ResMut<'static, Assets<ColorMaterial>>;
+
+    fn handle(&self) -> &Handle<GifAsset> {
+        &self.handle
+    }
+
+    fn set_handle(&mut self, handle: Handle<GifAsset>) {
+        self.handle = handle;
+    }
+
+    fn initialize(
+        &self,
+        (material, shared): &mut (Mut<'_, MeshMaterial2d<ColorMaterial>>, bool),
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+        _: &GifAsset,
+    ) {
+        if !*shared {
+            own_material(&mut material.0, materials);
+        }
+    }
+
+    /// In atlas mode, the uv transform selects the frame within the atlas.
+    fn show_frame(
+        &self,
+        (material, _): &mut (Mut<'_, MeshMaterial2d<ColorMaterial>>, bool),
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+        gif_asset: &GifAsset,
+        index: usize,
+    ) {
+        if let Some(mat) = materials.get_mut(&material.0) {
+            show_material_frame(
+                mat,
+                &[|mat, image| mat.texture = Some(image)],
+                Some(|mat, uv_transform| mat.uv_transform = uv_transform),
+                gif_asset,
+                index,
+            );
+        }
+    }
+}
+
+impl<M: Material> GifTarget for GifMaterial<M> {
+    type Data = (&'static mut MeshMaterial3d<M>, Has<GifSharedMaterial>);
+    type Param = ResMut<'static, Assets<M>>;
+
+    fn handle(&self) -> &Handle<GifAsset> {
+        &self.handle
+    }
+
+    fn set_handle(&mut self, handle: Handle<GifAsset>) {
+        self.handle = handle;
+    }
+
+    fn initialize(
+        &self,
+        (material, shared): &mut (Mut<'_, MeshMaterial3d<M>>, bool),
+        materials: &mut ResMut<Assets<M>>,
+        _: &GifAsset,
+    ) {
+        if !*shared {
+            own_material(&mut material.0, materials);
+        }
+    }
+
+    fn show_frame(
+        &self,
+        (material, _): &mut (Mut<'_, MeshMaterial3d<M>>, bool),
+        materials: &mut ResMut<Assets<M>>,
+        gif_asset: &GifAsset,
+        index: usize,
+    ) {
+        if let Some(mat) = materials.get_mut(&material.0) {
+            show_material_frame(mat, &self.slots, self.uv_transform, gif_asset, index);
+        }
+    }
+}
+
+/// Replace `handle` with a copy of its material, so that entities sharing a material
+/// do not fight over it while playing different frames.
+/// A new copy is made each time a GIF starts playing, the previous one being dropped with its handle.
+fn own_material<M: Asset + Clone>(handle: &mut Handle<M>, materials: &mut Assets<M>) {
+    if let Some(mat) = materials.get(&*handle).cloned() {
+        *handle = materials.add(mat);
+    }
+}
+
+/// Show a frame in each slot of a material. In atlas mode, the uv transform selects the frame within the atlas.
+fn show_material_frame<M>(
+    mat: &mut M,
+    slots: &[fn(&mut M, Handle<Image>)],
+    uv_transform: Option<fn(&mut M, Affine2)>,
+    gif_asset: &GifAsset,
+    index: usize,
+) {
+    let image = match &gif_asset.atlas {
+        Some(atlas) => {
+            if let Some(uv_transform) = uv_transform {
+                uv_transform(mat, atlas.uv_transform(index));
+            }
+            &atlas.image
+        }
+        None => &gif_asset.handles[index],
+    };
+    for slot in slots {
+        slot(mat, image.clone());
+    }
+}
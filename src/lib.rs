@@ -18,14 +18,14 @@
 //!
 //! App::default()
 //!     .add_plugins(DefaultPlugins)
-//!     .add_plugins(GifPlugin)
+//!     .add_plugins(GifPlugin::default())
 //!     .run();
 //! ```
 //!
 //! Spawn a [`Gif`]:
 //!
-//! ```no_run
-//! let handle = asset_loader.load("animated.gif") // located in ./assets/
+//! ```ignore
+//! let handle = asset_server.load("animated.gif"); // located in ./assets/
 //! commands.spawn(Gif { handle });
 //! ```
 //!
@@ -38,4 +38,11 @@
 //! The [examples](https://github.com/Chocorean/bevy-easy-gif/tree/main/examples) cover pretty much all there is to know.
 
 mod gif;
-pub use crate::gif::{Gif, Gif3d, GifAsset, GifDespawn, GifNode, GifPlayer, GifPlugin};
+pub use crate::gif::{
+    Gif, Gif2dMesh, Gif3d, GifAccessibility, GifAnimator, GifAppExt, GifAsset, GifAtlas, GifClock,
+    GifCondition, GifCulling, GifDespawn, GifDirection, GifEmitFrameChanged, GifEssential,
+    GifFinished, GifFrameChanged, GifFrameEvent, GifKeepProgress, GifLoaderSettings,
+    GifLoopCompleted, GifMaterial, GifNode, GifOnFinish, GifPlayer, GifPlugin, GifRepeat, GifRng,
+    GifSettings, GifSharedMaterial, GifStartOffset, GifState, GifSyncGroup, GifSystems, GifTarget,
+    GifTransition, GifTransitionTiming,
+};